@@ -0,0 +1,49 @@
+use alloy::primitives::{keccak256, Address};
+use alloy::signers::local::PrivateKeySigner;
+
+/// Anvil's first dev account under the default mnemonic. Deploying from it
+/// puts the first contract at 0x5FbDB2315678afecb367f032d93F642f64180aa3.
+const DEPLOYER_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+const DEPLOYER: &str = "deployer";
+
+/// A test account with a known private key, identified by name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestAccount {
+    pub name: String,
+    pub address: Address,
+    /// 0x-prefixed hex, the form the live feeder and alloy signers take
+    pub private_key: String,
+}
+
+impl TestAccount {
+    /// The account contracts are deployed and the live feeder's transfers are sent from
+    pub fn deployer() -> Self {
+        let signer: PrivateKeySigner = DEPLOYER_KEY.parse().expect("deployer key is valid");
+        Self::from_signer(DEPLOYER, &signer)
+    }
+
+    /// The account called `name`. Keys are derived from the name, so a name
+    /// is the same address on every chain and in every run.
+    pub fn named(name: &str) -> Self {
+        if name == DEPLOYER {
+            return Self::deployer();
+        }
+        let seed = keccak256(format!("rindexer-e2e account {}", name));
+        // A keccak output is outside the secp256k1 key range with negligible probability
+        let signer = PrivateKeySigner::from_bytes(&seed).expect("derived key is valid");
+        Self::from_signer(name, &signer)
+    }
+
+    fn from_signer(name: &str, signer: &PrivateKeySigner) -> Self {
+        Self {
+            name: name.to_string(),
+            address: signer.address(),
+            private_key: format!("0x{}", hex::encode(signer.to_bytes())),
+        }
+    }
+
+    pub fn signer(&self) -> PrivateKeySigner {
+        self.private_key.parse().expect("test account keys are valid")
+    }
+}
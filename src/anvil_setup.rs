@@ -1,67 +1,405 @@
 use std::time::Duration;
 use std::process::Stdio;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tokio::time::sleep;
 use anyhow::{Result, Context};
-use tracing::{info, debug, error};
+use tracing::info;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command as TokioCommand;
+use alloy::dyn_abi::DynSolValue;
+use alloy::primitives::{Address, B256, Bytes, U256};
+use nix::sys::signal::Signal;
+
+use crate::accounts::TestAccount;
+use crate::ports::allocate_free_port;
+use crate::contracts::{ContractDeployer, Deployment};
+use crate::fixtures::{Fixture, FixtureManifest};
+use crate::process_group::spawn_tracked;
+use crate::process_log::ProcessLog;
+use crate::supervisor::{ProcessState, SupervisedChild};
+
+/// keccak256("Transfer(address,address,uint256)"), topic0 of every ERC20 Transfer log
+pub const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
 
 pub struct AnvilInstance {
     pub rpc_url: String,
-    pub process: Option<tokio::process::Child>,
+    /// Anvil serves JSON-RPC over WebSocket on the same port as HTTP
+    pub ws_url: String,
+    pub port: u16,
+    pub chain_id: u64,
+    /// Killed with its process group when the instance is dropped
+    pub process: Option<SupervisedChild>,
+    /// Starting balance of pool accounts, in ether
+    balance: u64,
+    /// Named accounts funded on this chain so far
+    accounts: Mutex<BTreeMap<String, TestAccount>>,
+    /// What the chain was booted with when started from a fixture
+    pub fixture: Option<FixtureManifest>,
+}
+
+/// How Anvil produces blocks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mining {
+    /// Mine a block every interval, whether or not there are transactions
+    Interval(Duration),
+    /// Mine a block for every transaction as soon as it arrives (Anvil's default)
+    Auto,
+    /// Only mine when asked to through `evm_mine`/`anvil_mine`. Anything that
+    /// waits for a receipt, such as a contract deployment, hangs until a block is mined.
+    Manual,
+}
+
+/// Launch options for an Anvil instance.
+///
+/// Defaults match what the suite has always used: chain id 31337, ten
+/// accounts with 10000 ETH each and a block every second.
+#[derive(Debug, Clone)]
+pub struct AnvilOptions {
+    /// `None` picks a free port
+    pub port: Option<u16>,
+    pub chain_id: u64,
+    pub mining: Mining,
+    pub mnemonic: Option<String>,
+    pub accounts: u32,
+    /// Starting balance of every dev account, in ether
+    pub balance: u64,
+    pub gas_limit: u64,
+    pub gas_price: u64,
+    pub base_fee: Option<u64>,
+    pub hardfork: Option<String>,
+    /// Loaded on start if it exists and written back on exit (`anvil --state`)
+    pub state_path: Option<PathBuf>,
+    pub code_size_limit: Option<u64>,
+    /// Named fixture (see `FixtureSpec::named`) whose state the chain starts from
+    pub fixture: Option<String>,
+    /// Written when Anvil exits gracefully (`anvil --dump-state`)
+    pub dump_state: Option<PathBuf>,
+    /// RPC URL of the chain to fork (`anvil --fork-url`)
+    pub fork_url: Option<String>,
+    /// Last upstream block the fork shares; `None` forks at the upstream head
+    pub fork_block: Option<u64>,
+}
+
+impl Default for AnvilOptions {
+    fn default() -> Self {
+        Self {
+            port: None,
+            chain_id: 31337,
+            mining: Mining::Interval(Duration::from_secs(1)),
+            mnemonic: None,
+            accounts: 10,
+            balance: 10000,
+            gas_limit: 30_000_000,
+            gas_price: 1_000_000_000,
+            base_fee: None,
+            hardfork: None,
+            state_path: None,
+            code_size_limit: None,
+            fixture: None,
+            dump_state: None,
+            fork_url: None,
+            fork_block: None,
+        }
+    }
+}
+
+impl AnvilOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    pub fn with_mining(mut self, mining: Mining) -> Self {
+        self.mining = mining;
+        self
+    }
+
+    pub fn with_mnemonic(mut self, mnemonic: &str) -> Self {
+        self.mnemonic = Some(mnemonic.to_string());
+        self
+    }
+
+    pub fn with_accounts(mut self, accounts: u32, balance: u64) -> Self {
+        self.accounts = accounts;
+        self.balance = balance;
+        self
+    }
+
+    pub fn with_gas(mut self, gas_limit: u64, gas_price: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self.gas_price = gas_price;
+        self
+    }
+
+    pub fn with_base_fee(mut self, base_fee: u64) -> Self {
+        self.base_fee = Some(base_fee);
+        self
+    }
+
+    pub fn with_hardfork(mut self, hardfork: &str) -> Self {
+        self.hardfork = Some(hardfork.to_string());
+        self
+    }
+
+    pub fn with_code_size_limit(mut self, limit: u64) -> Self {
+        self.code_size_limit = Some(limit);
+        self
+    }
+
+    /// Boot from the named fixture, building it first if it is not cached
+    pub fn with_fixture(mut self, name: &str) -> Self {
+        self.fixture = Some(name.to_string());
+        self
+    }
+
+    /// Load `path` on start if it exists and write the chain back to it on exit
+    pub fn with_state(mut self, path: &Path) -> Self {
+        self.state_path = Some(path.to_path_buf());
+        self
+    }
+
+    pub fn with_dump_state(mut self, path: &Path) -> Self {
+        self.dump_state = Some(path.to_path_buf());
+        self
+    }
+
+    /// Fork the chain at `rpc_url` at `block`
+    pub fn with_fork(mut self, rpc_url: &str, block: Option<u64>) -> Self {
+        self.fork_url = Some(rpc_url.to_string());
+        self.fork_block = block;
+        self
+    }
+
+    fn args(&self, port: u16) -> Vec<String> {
+        let mut args = vec![
+            "--port".to_string(), port.to_string(),
+            "--chain-id".to_string(), self.chain_id.to_string(),
+            "--accounts".to_string(), self.accounts.to_string(),
+            "--balance".to_string(), self.balance.to_string(),
+            "--gas-limit".to_string(), self.gas_limit.to_string(),
+            "--gas-price".to_string(), self.gas_price.to_string(),
+        ];
+        match self.mining {
+            Mining::Interval(interval) => {
+                args.extend(["--block-time".to_string(), interval.as_secs_f64().to_string()]);
+            }
+            Mining::Auto => {}
+            Mining::Manual => args.push("--no-mining".to_string()),
+        }
+        if let Some(mnemonic) = &self.mnemonic {
+            args.extend(["--mnemonic".to_string(), mnemonic.clone()]);
+        }
+        if let Some(base_fee) = self.base_fee {
+            args.extend(["--base-fee".to_string(), base_fee.to_string()]);
+        }
+        if let Some(hardfork) = &self.hardfork {
+            args.extend(["--hardfork".to_string(), hardfork.clone()]);
+        }
+        if let Some(state_path) = &self.state_path {
+            args.extend(["--state".to_string(), state_path.display().to_string()]);
+        }
+        if let Some(limit) = self.code_size_limit {
+            args.extend(["--code-size-limit".to_string(), limit.to_string()]);
+        }
+        if let Some(dump_state) = &self.dump_state {
+            args.extend(["--dump-state".to_string(), dump_state.display().to_string()]);
+        }
+        if let Some(fork_url) = &self.fork_url {
+            args.extend(["--fork-url".to_string(), fork_url.clone()]);
+            if let Some(block) = self.fork_block {
+                args.extend(["--fork-block-number".to_string(), block.to_string()]);
+            }
+        }
+        args
+    }
+}
+
+/// A log on the chain, identified the way indexed rows are compared against it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChainLog {
+    pub block_number: u64,
+    /// Lowercase 0x-prefixed hashes
+    pub block_hash: String,
+    pub tx_hash: String,
+    pub log_index: u64,
+}
+
+impl ChainLog {
+    fn from_rpc(log: &serde_json::Value) -> Result<Self> {
+        let field = |name: &str| log[name].as_str()
+            .ok_or_else(|| anyhow::anyhow!("Log is missing {}: {}", name, log));
+        let quantity = |name: &str| -> Result<u64> {
+            Ok(u64::from_str_radix(field(name)?.trim_start_matches("0x"), 16)?)
+        };
+        Ok(Self {
+            block_number: quantity("blockNumber")?,
+            block_hash: field("blockHash")?.to_lowercase(),
+            tx_hash: field("transactionHash")?.to_lowercase(),
+            log_index: quantity("logIndex")?,
+        })
+    }
+}
+
+/// A transaction mined into the replacement chain by `AnvilInstance::reorg`
+#[derive(Debug, Clone)]
+pub struct ReorgTransaction {
+    /// JSON transaction request; `from` must be an unlocked dev account
+    pub request: serde_json::Value,
+    /// Block of the new chain it goes into, counted from the first replaced block
+    pub block_offset: u64,
+}
+
+impl ReorgTransaction {
+    /// ERC20 `transfer(to, amount)` on `token` sent by `from`
+    pub fn erc20_transfer(from: &str, token: &str, to: &str, amount: u64, block_offset: u64) -> Self {
+        Self {
+            request: erc20_transfer_request(from, token, to, amount),
+            block_offset,
+        }
+    }
+}
+
+/// JSON request for ERC20 `transfer(to, amount)` on `token` sent by `from`,
+/// for Anvil to sign (`send_unsigned`, `anvil_reorg`)
+pub fn erc20_transfer_request(from: &str, token: &str, to: &str, amount: u64) -> serde_json::Value {
+    let to = to.trim_start_matches("0x");
+    serde_json::json!({
+        "from": from,
+        "to": token,
+        "data": format!("0xa9059cbb{:0>64}{:064x}", to, amount),
+        "gas": "0x186a0"
+    })
 }
 
 impl AnvilInstance {
-    pub async fn start_local(private_key: &str) -> Result<Self> {
-        info!("Starting local Anvil instance");
+    /// Start Anvil with `options`, capturing its output into `log`
+    pub async fn start(options: AnvilOptions, log: ProcessLog) -> Result<Self> {
+        let port = match options.port {
+            Some(port) => port,
+            None => allocate_free_port()?,
+        };
+        let fixture = match &options.fixture {
+            // Building a fixture starts an Anvil of its own
+            Some(name) => Some(Box::pin(Fixture::ensure(name)).await
+                .with_context(|| format!("Failed to prepare fixture {}", name))?),
+            None => None,
+        };
+        let mut args = options.args(port);
+        if let Some(fixture) = &fixture {
+            if fixture.manifest.chain_id != options.chain_id {
+                return Err(anyhow::anyhow!(
+                    "Fixture {} was built on chain {}, not {}",
+                    fixture.manifest.spec.name, fixture.manifest.chain_id, options.chain_id
+                ));
+            }
+            args.extend(["--load-state".to_string(), fixture.state_path.display().to_string()]);
+        }
+        info!("Starting local Anvil instance on port {} (chain id {})", port, options.chain_id);
         
         let mut cmd = TokioCommand::new("anvil");
-        cmd.arg("--chain-id")
-           .arg("31337")
-           .arg("--accounts")
-           .arg("10")
-           .arg("--balance")
-           .arg("10000")
-           .arg("--gas-limit")
-           .arg("30000000")
-           .arg("--gas-price")
-           .arg("1000000000")
-           .arg("--block-time")
-           .arg("1")
+        cmd.args(args)
            .stdout(Stdio::piped())
            .stderr(Stdio::piped());
         
-        let mut child = cmd.spawn()
+        let mut child = spawn_tracked(&mut cmd, &log)
             .context("Failed to start Anvil")?;
         
         // Start log streaming for Anvil
-        Self::start_log_streaming(&mut child).await;
+        Self::start_log_streaming(&mut child, log.clone()).await;
+        let process = SupervisedChild::spawn(child, log)?;
         
         // Wait a bit for Anvil to start
         sleep(Duration::from_millis(500)).await;
         
         // Check if process is still running
-        match child.try_wait()? {
-            Some(status) => {
-                return Err(anyhow::anyhow!("Anvil exited with status: {}", status));
-            }
-            None => {
-                info!("Anvil process started successfully");
-            }
+        if let ProcessState::Exited { status, .. } = process.state() {
+            let status = status.map(|s| s.to_string()).unwrap_or_else(|| "unknown".to_string());
+            return Err(anyhow::anyhow!("Anvil exited with status: {}", status));
         }
+        info!("Anvil process started successfully");
         
-        let rpc_url = "http://127.0.0.1:8545".to_string();
+        let rpc_url = format!("http://127.0.0.1:{}", port);
+        let ws_url = format!("ws://127.0.0.1:{}", port);
         
         // Wait for RPC to be ready
         Self::wait_for_rpc_ready(&rpc_url).await?;
         
-        // Fund the test account
-        Self::fund_test_account(&rpc_url, private_key).await?;
-        
-        Ok(Self {
+        let instance = Self {
             rpc_url,
-            process: Some(child),
-        })
+            ws_url,
+            port,
+            chain_id: options.chain_id,
+            process: Some(process),
+            balance: options.balance,
+            accounts: Mutex::new(BTreeMap::new()),
+            fixture: fixture.map(|f| f.manifest),
+        };
+        if let Some(manifest) = &instance.fixture {
+            instance.check_fixture_loaded(manifest).await?;
+        }
+        // A custom mnemonic does not include the deployer's key
+        instance.account("deployer").await?;
+        Ok(instance)
+    }
+    
+    /// Start Anvil as a fork of `upstream` at `block`. Blocks up to `block`
+    /// are served from `upstream`; the fork mines its own blocks after it and
+    /// reports `options.chain_id`, so it can be indexed next to its parent.
+    pub async fn start_fork(upstream: &AnvilInstance, block: u64, options: AnvilOptions, log: ProcessLog) -> Result<Self> {
+        let head = upstream.get_block_number().await?;
+        if block > head {
+            return Err(anyhow::anyhow!("Cannot fork at block {}, upstream head is {}", block, head));
+        }
+        info!("Forking {} at block {}", upstream.rpc_url, block);
+        Self::start(options.with_fork(&upstream.rpc_url, Some(block)), log).await
+    }
+    
+    /// Stop Anvil with SIGINT so it can write its `--dump-state` file,
+    /// killing it if it takes longer than `timeout`
+    pub async fn stop_gracefully(&mut self, timeout: Duration) -> Result<()> {
+        if let Some(process) = self.process.take() {
+            info!("Stopping Anvil instance gracefully");
+            let outcome = process.shutdown(Signal::SIGINT, timeout).await?;
+            if outcome.escalated {
+                return Err(anyhow::anyhow!("Anvil did not exit within {:?} of SIGINT", timeout));
+            }
+        }
+        Ok(())
+    }
+    
+    /// The loaded state must include the fixture's history, not just its
+    /// accounts; Anvil versions without block dumps only restore the latter
+    async fn check_fixture_loaded(&self, manifest: &FixtureManifest) -> Result<()> {
+        let head = self.get_block_number().await?;
+        let transfers = self.count_logs(&manifest.contract_address, TRANSFER_TOPIC).await? as u64;
+        if head < manifest.head_block || transfers != manifest.transfer_events {
+            return Err(anyhow::anyhow!(
+                "Fixture {} loaded at block {} with {} Transfer logs, expected block {} with {}",
+                manifest.spec.name, head, transfers, manifest.head_block, manifest.transfer_events
+            ));
+        }
+        info!("Loaded fixture {}: {} Transfer logs up to block {}", manifest.spec.name, transfers, head);
+        Ok(())
+    }
+    
+    /// Kill the Anvil process group and wait for it to exit
+    pub async fn stop(&mut self) -> Result<()> {
+        if let Some(process) = self.process.take() {
+            info!("Stopping Anvil instance");
+            process.stop(Duration::from_secs(5)).await?;
+        }
+        Ok(())
     }
     
     async fn wait_for_rpc_ready(rpc_url: &str) -> Result<()> {
@@ -92,13 +430,6 @@ impl AnvilInstance {
         Err(anyhow::anyhow!("Anvil RPC failed to become ready after {} attempts", MAX_ATTEMPTS))
     }
     
-    async fn fund_test_account(_rpc_url: &str, _private_key: &str) -> Result<()> {
-        //TODO This would typically fund accounts for testing
-        // For now, we'll use the default funded accounts from Anvil
-        info!("Using default Anvil funded accounts");
-        Ok(())
-    }
-    
     pub async fn mine_block(&self) -> Result<()> {
         let client = reqwest::Client::new();
         
@@ -140,14 +471,217 @@ impl AnvilInstance {
         Ok(block_number)
     }
     
-    async fn start_log_streaming(child: &mut tokio::process::Child) {
+    pub async fn get_chain_id(&self) -> Result<u64> {
+        let result = self.rpc("eth_chainId", serde_json::json!([])).await?;
+        let hex_value = result.as_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid response format"))?;
+        
+        Ok(u64::from_str_radix(hex_value.trim_start_matches("0x"), 16)?)
+    }
+    
+    /// Number of logs emitted by `address` with first topic `topic0` over the whole chain
+    pub async fn count_logs(&self, address: &str, topic0: &str) -> Result<usize> {
+        Ok(self.get_logs(address, topic0).await?.len())
+    }
+    
+    /// Every log emitted by `address` with first topic `topic0` on the current canonical chain
+    pub async fn get_logs(&self, address: &str, topic0: &str) -> Result<Vec<ChainLog>> {
+        let result = self.rpc("eth_getLogs", serde_json::json!([{
+            "address": address,
+            "topics": [topic0],
+            "fromBlock": "0x0",
+            "toBlock": "latest"
+        }])).await?;
+        let logs = result.as_array()
+            .ok_or_else(|| anyhow::anyhow!("Invalid eth_getLogs response: {}", result))?;
+        logs.iter().map(ChainLog::from_rpc).collect()
+    }
+    
+    /// Hash of block `number` on the current canonical chain
+    pub async fn get_block_hash(&self, number: u64) -> Result<String> {
+        let block = self.rpc("eth_getBlockByNumber", serde_json::json!([format!("{:#x}", number), false])).await?;
+        block["hash"].as_str()
+            .map(str::to_lowercase)
+            .ok_or_else(|| anyhow::anyhow!("Block {} not found", number))
+    }
+    
+    /// Timestamp of block `number` on the current canonical chain
+    pub async fn get_block_timestamp(&self, number: u64) -> Result<u64> {
+        let block = self.rpc("eth_getBlockByNumber", serde_json::json!([format!("{:#x}", number), false])).await?;
+        let timestamp = block["timestamp"].as_str()
+            .ok_or_else(|| anyhow::anyhow!("Block {} not found", number))?;
+        Ok(u64::from_str_radix(timestamp.trim_start_matches("0x"), 16)?)
+    }
+    
+    /// Take an `evm_snapshot` of the current chain state
+    pub async fn snapshot(&self) -> Result<String> {
+        let id = self.rpc("evm_snapshot", serde_json::json!([])).await?;
+        id.as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Invalid evm_snapshot response: {}", id))
+    }
+    
+    /// Roll the chain back to snapshot `id`, dropping every block mined since.
+    /// Blocks mined afterwards replace them at the same heights.
+    pub async fn revert(&self, id: &str) -> Result<()> {
+        let reverted = self.rpc("evm_revert", serde_json::json!([id])).await?;
+        if reverted.as_bool() != Some(true) {
+            return Err(anyhow::anyhow!("evm_revert to snapshot {} failed: {}", id, reverted));
+        }
+        Ok(())
+    }
+    
+    /// Replace the last `depth` blocks with `depth` new blocks holding
+    /// `transactions` instead of the original ones (`anvil_reorg`)
+    pub async fn reorg(&self, depth: u64, transactions: &[ReorgTransaction]) -> Result<()> {
+        let pairs: Vec<serde_json::Value> = transactions.iter()
+            .map(|tx| serde_json::json!([tx.request, tx.block_offset]))
+            .collect();
+        info!("Reorganizing the last {} blocks with {} replacement transactions", depth, transactions.len());
+        self.rpc("anvil_reorg", serde_json::json!([{ "depth": depth, "txBlockPairs": pairs }])).await?;
+        Ok(())
+    }
+    
+    /// The account contracts are deployed from; funded when Anvil starts
+    pub fn deployer(&self) -> TestAccount {
+        TestAccount::deployer()
+    }
+    
+    /// The named test account `name`, funded with the chain's starting
+    /// balance the first time it is asked for on this chain
+    pub async fn account(&self, name: &str) -> Result<TestAccount> {
+        if let Some(account) = self.accounts.lock().unwrap().get(name) {
+            return Ok(account.clone());
+        }
+        let account = TestAccount::named(name);
+        let wei = U256::from(self.balance) * U256::from(10u64).pow(U256::from(18u64));
+        self.set_balance(account.address, wei).await
+            .with_context(|| format!("Failed to fund test account {}", name))?;
+        info!("Funded test account {} ({}) with {} ETH", name, account.address, self.balance);
+        self.accounts.lock().unwrap().insert(name.to_string(), account.clone());
+        Ok(account)
+    }
+    
+    /// Set the ETH balance of `address` to `wei`
+    pub async fn set_balance(&self, address: Address, wei: U256) -> Result<()> {
+        self.rpc("anvil_setBalance", serde_json::json!([address, wei])).await?;
+        Ok(())
+    }
+    
+    /// Let `send_unsigned` send transactions from `address` without its key
+    pub async fn impersonate(&self, address: Address) -> Result<()> {
+        self.rpc("anvil_impersonateAccount", serde_json::json!([address])).await?;
+        Ok(())
+    }
+    
+    pub async fn stop_impersonating(&self, address: Address) -> Result<()> {
+        self.rpc("anvil_stopImpersonatingAccount", serde_json::json!([address])).await?;
+        Ok(())
+    }
+    
+    /// Send `request` for Anvil to sign (`eth_sendTransaction`). Only works
+    /// from dev accounts and impersonated addresses. Returns the transaction hash.
+    pub async fn send_unsigned(&self, request: serde_json::Value) -> Result<String> {
+        let hash = self.rpc("eth_sendTransaction", serde_json::json!([request])).await?;
+        hash.as_str()
+            .map(str::to_lowercase)
+            .ok_or_else(|| anyhow::anyhow!("Invalid eth_sendTransaction response: {}", hash))
+    }
+    
+    /// Send several `eth_sendTransaction` requests in one JSON-RPC batch
+    pub async fn send_unsigned_batch(&self, requests: &[serde_json::Value]) -> Result<Vec<String>> {
+        let batch: Vec<serde_json::Value> = requests.iter().enumerate()
+            .map(|(id, request)| serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_sendTransaction",
+                "params": [request],
+                "id": id
+            }))
+            .collect();
+        let response = reqwest::Client::new().post(&self.rpc_url)
+            .json(&batch)
+            .send()
+            .await?;
+        let mut results: Vec<serde_json::Value> = response.json().await?;
+        results.sort_by_key(|r| r["id"].as_u64());
+        results.iter().map(|result| {
+            if let Some(error) = result.get("error") {
+                return Err(anyhow::anyhow!("eth_sendTransaction failed: {}", error));
+            }
+            result["result"].as_str()
+                .map(str::to_lowercase)
+                .ok_or_else(|| anyhow::anyhow!("Invalid eth_sendTransaction response: {}", result))
+        }).collect()
+    }
+    
+    /// Turn mining on every transaction on or off; `mine_n` still mines
+    pub async fn set_automine(&self, enabled: bool) -> Result<()> {
+        self.rpc("evm_setAutomine", serde_json::json!([enabled])).await?;
+        Ok(())
+    }
+    
+    /// Replace the runtime code at `address`
+    pub async fn set_code(&self, address: Address, code: &Bytes) -> Result<()> {
+        self.rpc("anvil_setCode", serde_json::json!([address, code])).await?;
+        Ok(())
+    }
+    
+    /// Write `value` to storage `slot` of `address`
+    pub async fn set_storage_at(&self, address: Address, slot: U256, value: U256) -> Result<()> {
+        self.rpc("anvil_setStorageAt", serde_json::json!([address, slot, B256::from(value)])).await?;
+        Ok(())
+    }
+    
+    pub async fn set_nonce(&self, address: Address, nonce: u64) -> Result<()> {
+        self.rpc("anvil_setNonce", serde_json::json!([address, format!("{:#x}", nonce)])).await?;
+        Ok(())
+    }
+    
+    /// Mine `blocks` blocks at once, whatever the mining mode
+    pub async fn mine_n(&self, blocks: u64) -> Result<()> {
+        self.rpc("anvil_mine", serde_json::json!([format!("{:#x}", blocks)])).await?;
+        Ok(())
+    }
+    
+    /// Move the clock forward by `seconds` for every following block
+    pub async fn increase_time(&self, seconds: u64) -> Result<()> {
+        self.rpc("evm_increaseTime", serde_json::json!([seconds])).await?;
+        Ok(())
+    }
+    
+    /// Give the next mined block `timestamp`; later blocks continue from there
+    pub async fn set_next_block_timestamp(&self, timestamp: u64) -> Result<()> {
+        self.rpc("evm_setNextBlockTimestamp", serde_json::json!([timestamp])).await?;
+        Ok(())
+    }
+    
+    async fn rpc(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let response = reqwest::Client::new().post(&self.rpc_url)
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": params,
+                "id": 1
+            }))
+            .send()
+            .await?;
+        
+        let mut result: serde_json::Value = response.json().await?;
+        if let Some(error) = result.get("error") {
+            return Err(anyhow::anyhow!("{} failed: {}", method, error));
+        }
+        Ok(result["result"].take())
+    }
+    
+    async fn start_log_streaming(child: &mut tokio::process::Child, log: ProcessLog) {
         if let Some(stdout) = child.stdout.take() {
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
+            let log = log.clone();
             
             tokio::spawn(async move {
                 while let Ok(Some(line)) = lines.next_line().await {
-                    debug!("[ANVIL] {}", line);
+                    log.push_stdout(&line);
                 }
             });
         }
@@ -158,7 +692,7 @@ impl AnvilInstance {
             
             tokio::spawn(async move {
                 while let Ok(Some(line)) = lines.next_line().await {
-                    error!("[ANVIL ERROR] {}", line);
+                    log.push_stderr(&line);
                 }
             });
         }
@@ -166,48 +700,16 @@ impl AnvilInstance {
 }
 
 impl AnvilInstance {
-    /// Deploy a test contract using forge
-    pub async fn deploy_test_contract(&self) -> Result<String> {
-        info!("Deploying SimpleERC20 test contract...");
-        
-        let output = std::process::Command::new("forge")
-            .args([
-                "create",
-                "--rpc-url", &self.rpc_url,
-                "--private-key", "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
-                "--broadcast",
-                "contracts/SimpleERC20.sol:SimpleERC20"
-            ])
-            .output()
-            .context("Failed to run forge command")?;
-        
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!("Contract deployment failed: {}", stderr));
-        }
-        
-        // Parse the contract address from forge output
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let address_line = stdout.lines()
-            .find(|line| line.contains("Deployed to:"))
-            .ok_or_else(|| anyhow::anyhow!("Could not find contract address in forge output"))?;
-        
-        let address = address_line.split_whitespace()
-            .last()
-            .ok_or_else(|| anyhow::anyhow!("Could not parse contract address"))?;
-        
-        info!("Test contract deployed at: {}", address);
-        Ok(address.to_string())
+    /// Deploy `name` from its checked-in artifact (forge builds it when there is none)
+    pub async fn deploy_contract(&self, name: &str, constructor_args: &[DynSolValue], log: ProcessLog) -> Result<Deployment> {
+        ContractDeployer::new(&self.rpc_url, &self.deployer().private_key)
+            .with_log(log)
+            .deploy(name, constructor_args)
+            .await
     }
-}
 
-impl Drop for AnvilInstance {
-    fn drop(&mut self) {
-        if let Some(mut child) = self.process.take() {
-            info!("Shutting down Anvil instance");
-            let _ = child.kill();
-            // Note: tokio::process::Child doesn't have wait_timeout, 
-            // but the process will be cleaned up when the child is dropped
-        }
+    /// Deploy SimpleERC20, the contract the live feeder transfers on
+    pub async fn deploy_test_contract(&self, log: ProcessLog) -> Result<String> {
+        Ok(self.deploy_contract("SimpleERC20", &[], log).await?.address)
     }
 }
@@ -0,0 +1,131 @@
+use anyhow::{Result, Context};
+use std::path::Path;
+use std::process::Command;
+use tracing::{info, warn};
+
+use crate::test_suite::TestContext;
+
+/// Capture everything needed to debug a failed test into `dest`.
+///
+/// Runs before teardown so the processes and containers are still alive.
+/// Individual pieces that cannot be collected are listed in
+/// `collection-errors.txt` instead of aborting the whole bundle.
+pub async fn collect_failure_artifacts(context: &TestContext, dest: &Path) -> Result<()> {
+    if dest.exists() {
+        std::fs::remove_dir_all(dest)
+            .with_context(|| format!("Failed to clear old artifacts at {}", dest.display()))?;
+    }
+    std::fs::create_dir_all(dest)
+        .with_context(|| format!("Failed to create artifacts directory {}", dest.display()))?;
+
+    let mut errors = Vec::new();
+
+    // Project configuration and ABIs as rindexer saw them
+    let config_path = context.project_path.join("rindexer.yaml");
+    if config_path.exists() {
+        if let Err(e) = std::fs::copy(&config_path, dest.join("rindexer.yaml")) {
+            errors.push(format!("rindexer.yaml: {}", e));
+        }
+    }
+    if let Err(e) = copy_dir_if_exists(&context.project_path.join("abis"), &dest.join("abis")) {
+        errors.push(format!("abis: {}", e));
+    }
+
+    // Indexed output
+    if let Err(e) = copy_dir_if_exists(&context.get_csv_output_path(), &dest.join("generated_csv")) {
+        errors.push(format!("generated_csv: {}", e));
+    }
+
+    // Process output (Anvil and every rindexer instance the test started).
+    // The log file is complete; the in-memory buffer may have dropped old lines.
+    let logs_dir = dest.join("logs");
+    for log in context.logs.all() {
+        let target = logs_dir.join(format!("{}.log", log.name()));
+        let result = std::fs::create_dir_all(&logs_dir).and_then(|_| match log.path() {
+            Some(path) => std::fs::copy(path, &target).map(|_| ()),
+            None => {
+                let lines: Vec<String> = log.lines().iter().map(|l| l.to_string()).collect();
+                std::fs::write(&target, join_lines(&lines))
+            }
+        });
+        if let Err(e) = result {
+            errors.push(format!("{} logs: {}", log.name(), e));
+        }
+    }
+
+    // What rindexer reported, as parsed events
+    if let Some(rindexer) = &context.rindexer {
+        let events: Vec<String> = rindexer.events.all().iter().map(|e| format!("{:?}", e.event)).collect();
+        if let Err(e) = std::fs::write(dest.join("rindexer-events.txt"), join_lines(&events)) {
+            errors.push(format!("rindexer events: {}", e));
+        }
+    }
+
+    // Health endpoint snapshot
+    if let Some(health) = &context.health_client {
+        match health.get_health_raw().await {
+            Ok((status, body)) => {
+                if let Err(e) = std::fs::write(dest.join("health.json"), format!("HTTP {}\n{}", status, body)) {
+                    errors.push(format!("health snapshot: {}", e));
+                }
+            }
+            Err(e) => errors.push(format!("health snapshot: {}", e)),
+        }
+    }
+
+    // Postgres schema for every container the test used
+    for container in &context.postgres_containers {
+        match pg_dump_schema(container) {
+            Ok(schema) => {
+                if let Err(e) = std::fs::write(dest.join(format!("{}.schema.sql", container)), schema) {
+                    errors.push(format!("pg_dump {}: {}", container, e));
+                }
+            }
+            Err(e) => errors.push(format!("pg_dump {}: {}", container, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        warn!("Some failure artifacts could not be collected: {:?}", errors);
+        std::fs::write(dest.join("collection-errors.txt"), join_lines(&errors))?;
+    }
+
+    info!("Failure artifacts written to {}", dest.display());
+    Ok(())
+}
+
+fn join_lines(lines: &[String]) -> String {
+    let mut out = lines.join("\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+fn copy_dir_if_exists(src: &Path, dst: &Path) -> std::io::Result<()> {
+    if !src.exists() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_if_exists(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+fn pg_dump_schema(container: &str) -> Result<String> {
+    let output = Command::new("docker")
+        .args(["exec", container, "pg_dump", "-U", "postgres", "--schema-only", "postgres"])
+        .output()
+        .context("Failed to run docker exec pg_dump")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("pg_dump failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
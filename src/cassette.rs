@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+/// Checked-in cassettes, relative to the crate root like the expected CSVs in `data/`
+pub const CASSETTE_DIR: &str = "data/cassettes";
+
+/// Set to re-record cassettes from the live upstream instead of replaying them
+pub const RECORD_ENV: &str = "RECORD_CASSETTES";
+
+/// One JSON-RPC request and what the upstream answered it with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub params: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<serde_json::Value>,
+}
+
+/// Recorded JSON-RPC traffic, in the order the upstream answered it. Request ids
+/// and the upstream URL (which may carry an API key) are never stored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn path(name: &str) -> PathBuf {
+        Path::new(CASSETTE_DIR).join(format!("{}.json", name))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read cassette {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid cassette {:?}", path))
+    }
+
+    /// Write one interaction per line so re-recordings diff readably
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {:?}", parent))?;
+        }
+        let lines = self.interactions.iter()
+            .map(|interaction| serde_json::to_string(interaction).map(|line| format!("    {}", line)))
+            .collect::<serde_json::Result<Vec<String>>>()?;
+        let content = format!("{{\n  \"interactions\": [\n{}\n  ]\n}}\n", lines.join(",\n"));
+        std::fs::write(path, content).with_context(|| format!("Failed to write cassette {:?}", path))
+    }
+
+    /// Record every request of a request or batch `body` with its answer in `response`
+    pub fn record(&mut self, body: &[u8], response: &[u8]) {
+        let (Ok(request), Ok(response)) = (
+            serde_json::from_slice::<serde_json::Value>(body),
+            serde_json::from_slice::<serde_json::Value>(response),
+        ) else {
+            return;
+        };
+        let requests = match request {
+            serde_json::Value::Array(batch) => batch,
+            request => vec![request],
+        };
+        let responses = match response {
+            serde_json::Value::Array(batch) => batch,
+            response => vec![response],
+        };
+
+        for request in &requests {
+            let Some(method) = request["method"].as_str() else { continue };
+            // Batch responses may come back in any order
+            let Some(response) = responses.iter().find(|response| response["id"] == request["id"]) else {
+                continue;
+            };
+            self.interactions.push(Interaction {
+                method: method.to_string(),
+                params: request["params"].clone(),
+                result: response.get("result").cloned(),
+                error: response.get("error").cloned(),
+            });
+        }
+    }
+}
+
+/// How a test reaches a network it has a cassette for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Serve the checked-in cassette; no network needed
+    Replay(PathBuf),
+    /// Forward to the live upstream and write the cassette once the test passes
+    Record { upstream: String, path: PathBuf },
+    /// Forward to the live upstream; there is no cassette to replay yet
+    Live(String),
+}
+
+impl CassetteMode {
+    /// Replay cassette `name` when it exists, unless `RECORD_CASSETTES` asks for it
+    /// to be re-recorded from the URL in `upstream_env`
+    pub fn select(name: &str, upstream_env: &str) -> Result<Self> {
+        let path = Cassette::path(name);
+        let upstream = std::env::var(upstream_env).ok().filter(|url| !url.trim().is_empty());
+        let record = std::env::var(RECORD_ENV).is_ok_and(|value| !value.trim().is_empty());
+
+        match upstream {
+            Some(upstream) if record => Ok(CassetteMode::Record { upstream, path }),
+            None if record => Err(anyhow::anyhow!("{} is set but {} is not", RECORD_ENV, upstream_env)),
+            _ if path.exists() => Ok(CassetteMode::Replay(path)),
+            Some(upstream) => Ok(CassetteMode::Live(upstream)),
+            None => Err(anyhow::anyhow!("no cassette at {:?} and {} not set", path, upstream_env)),
+        }
+    }
+}
+
+/// `eth_getLogs` answers of a cassette for one filter, indexed by the blocks they cover
+struct RecordedLogs {
+    /// The filter without `fromBlock`/`toBlock`
+    filter: String,
+    range: RangeInclusive<u64>,
+    logs: Vec<serde_json::Value>,
+}
+
+/// Serves a cassette's answers. Identical requests get their recorded answers in
+/// order, the last one repeating once they run out. An `eth_getLogs` call that was
+/// not recorded as-is is answered from the recorded calls for the same filter
+/// when they cover its whole range, so rindexer may split ranges differently
+/// than it did while recording.
+pub struct Replay {
+    answers: HashMap<String, VecDeque<Interaction>>,
+    logs: Vec<RecordedLogs>,
+}
+
+impl Replay {
+    pub fn new(cassette: Cassette) -> Self {
+        let mut answers: HashMap<String, VecDeque<Interaction>> = HashMap::new();
+        let mut logs = Vec::new();
+        for interaction in cassette.interactions {
+            if interaction.method == "eth_getLogs" {
+                if let (Some(range), Some(result)) = (filter_range(&interaction.params), interaction.result.as_ref()) {
+                    if let Some(result) = result.as_array() {
+                        logs.push(RecordedLogs {
+                            filter: filter_key(&interaction.params),
+                            range,
+                            logs: result.clone(),
+                        });
+                    }
+                }
+            }
+            answers.entry(request_key(&interaction.method, &interaction.params))
+                .or_default()
+                .push_back(interaction);
+        }
+        Self { answers, logs }
+    }
+
+    /// The response to a single request, keeping its id; `None` when the
+    /// cassette has no answer for it
+    pub fn answer(&mut self, request: &serde_json::Value) -> Option<serde_json::Value> {
+        let method = request["method"].as_str()?;
+        let params = &request["params"];
+        let mut response = serde_json::json!({ "jsonrpc": "2.0", "id": request["id"] });
+
+        if let Some(queue) = self.answers.get_mut(&request_key(method, params)) {
+            let interaction = if queue.len() > 1 { queue.pop_front()? } else { queue.front()?.clone() };
+            match interaction.error {
+                Some(error) => response["error"] = error,
+                None => response["result"] = interaction.result.unwrap_or(serde_json::Value::Null),
+            }
+            return Some(response);
+        }
+
+        if method == "eth_getLogs" {
+            response["result"] = serde_json::Value::Array(self.covered_logs(params)?);
+            return Some(response);
+        }
+        None
+    }
+
+    /// Logs for an `eth_getLogs` filter whose range recorded calls fully cover
+    fn covered_logs(&self, params: &serde_json::Value) -> Option<Vec<serde_json::Value>> {
+        let wanted = filter_range(params)?;
+        let filter = filter_key(params);
+        let mut recorded: Vec<&RecordedLogs> = self.logs.iter()
+            .filter(|recorded| recorded.filter == filter)
+            .collect();
+        recorded.sort_by_key(|recorded| *recorded.range.start());
+
+        let mut next = *wanted.start();
+        for recorded in &recorded {
+            if *recorded.range.start() <= next && *recorded.range.end() >= next {
+                next = recorded.range.end().saturating_add(1);
+            }
+        }
+        if next <= *wanted.end() {
+            return None;
+        }
+
+        let mut seen = HashSet::new();
+        let mut logs: Vec<serde_json::Value> = recorded.iter()
+            .flat_map(|recorded| recorded.logs.iter())
+            .filter(|log| block_number(&log["blockNumber"]).is_some_and(|block| wanted.contains(&block)))
+            .filter(|log| seen.insert((log["blockHash"].to_string(), log["logIndex"].to_string())))
+            .cloned()
+            .collect();
+        logs.sort_by_key(|log| (block_number(&log["blockNumber"]), block_number(&log["logIndex"])));
+        Some(logs)
+    }
+}
+
+/// Requests match regardless of id and of the case of hex strings
+fn request_key(method: &str, params: &serde_json::Value) -> String {
+    format!("{} {}", method, params.to_string().to_lowercase())
+}
+
+fn filter_key(params: &serde_json::Value) -> String {
+    let mut filter = params[0].clone();
+    if let Some(filter) = filter.as_object_mut() {
+        filter.remove("fromBlock");
+        filter.remove("toBlock");
+    }
+    filter.to_string().to_lowercase()
+}
+
+/// Blocks of an `eth_getLogs` filter with numeric bounds
+fn filter_range(params: &serde_json::Value) -> Option<RangeInclusive<u64>> {
+    Some(block_number(&params[0]["fromBlock"])?..=block_number(&params[0]["toBlock"])?)
+}
+
+fn block_number(value: &serde_json::Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.strip_prefix("0x")?, 16).ok()
+}
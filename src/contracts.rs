@@ -0,0 +1,231 @@
+use anyhow::{Result, Context};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command as TokioCommand;
+use tracing::info;
+use alloy::{
+    dyn_abi::DynSolValue,
+    json_abi::JsonAbi,
+    network::{EthereumWallet, TransactionBuilder},
+    primitives::Bytes,
+    providers::{Provider, ProviderBuilder},
+    rpc::types::TransactionRequest,
+    signers::local::PrivateKeySigner,
+};
+
+use crate::process_group::{spawn_tracked, untrack_if_empty};
+use crate::process_log::ProcessLog;
+
+/// Checked-in artifacts, one `<Contract>.json` per contract
+pub const ARTIFACTS_DIR: &str = "contracts/artifacts";
+
+/// Solidity sources compiled with forge when a contract has no checked-in artifact
+pub const SOURCES_DIR: &str = "contracts";
+
+/// Where the forge fallback writes its build output
+const FORGE_OUT_DIR: &str = "target/forge-out";
+
+/// ABI and creation bytecode of a contract.
+///
+/// Artifacts use forge's output layout (`abi`, `bytecode.object`); `make artifacts`
+/// copies them from `forge build`'s `out/` directory.
+#[derive(Debug, Clone)]
+pub struct ContractArtifact {
+    pub name: String,
+    pub abi: JsonAbi,
+    pub bytecode: Bytes,
+    pub source: ArtifactSource,
+}
+
+/// Where a contract's artifact came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactSource {
+    /// `contracts/artifacts/<Contract>.json`, no forge involved
+    CheckedIn,
+    /// Built with `forge build` because there was no checked-in artifact
+    Forge,
+}
+
+impl ContractArtifact {
+    /// The checked-in artifact for `name`, or `None` when there is none
+    pub fn load(name: &str) -> Result<Option<Self>> {
+        let path = artifact_path(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::from_file(name, &path, ArtifactSource::CheckedIn).map(Some)
+    }
+
+    pub fn from_file(name: &str, path: &Path, source: ArtifactSource) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read artifact {}", path.display()))?;
+        let json: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Artifact {} is not valid JSON", path.display()))?;
+
+        let abi: JsonAbi = serde_json::from_value(json["abi"].clone())
+            .with_context(|| format!("Artifact {} has no valid abi", path.display()))?;
+        let bytecode = json["bytecode"]["object"].as_str()
+            .ok_or_else(|| anyhow::anyhow!("Artifact {} has no bytecode.object", path.display()))?;
+        let bytecode: Bytes = bytecode.parse()
+            .with_context(|| format!("Artifact {} has malformed bytecode", path.display()))?;
+        if bytecode.is_empty() {
+            return Err(anyhow::anyhow!("Artifact {} has empty bytecode (abstract contract or interface?)", path.display()));
+        }
+
+        Ok(Self { name: name.to_string(), abi, bytecode, source })
+    }
+
+    /// Creation code followed by the ABI-encoded constructor arguments
+    pub fn deploy_code(&self, constructor_args: &[DynSolValue]) -> Result<Bytes> {
+        let expected = self.abi.constructor.as_ref().map_or(0, |c| c.inputs.len());
+        if constructor_args.len() != expected {
+            return Err(anyhow::anyhow!(
+                "{} constructor takes {} arguments, got {}",
+                self.name, expected, constructor_args.len()
+            ));
+        }
+        let mut code = self.bytecode.to_vec();
+        if !constructor_args.is_empty() {
+            code.extend(DynSolValue::Tuple(constructor_args.to_vec()).abi_encode_params());
+        }
+        Ok(code.into())
+    }
+}
+
+/// Whether `name` can be deployed without forge
+pub fn has_artifact(name: &str) -> bool {
+    artifact_path(name).exists()
+}
+
+fn artifact_path(name: &str) -> PathBuf {
+    Path::new(ARTIFACTS_DIR).join(format!("{}.json", name))
+}
+
+/// A contract creation that has been mined
+#[derive(Debug, Clone)]
+pub struct Deployment {
+    pub address: String,
+    pub tx_hash: String,
+    pub block_number: u64,
+    /// Whether the deployed bytecode came from the checked-in artifact or forge
+    pub source: ArtifactSource,
+}
+
+/// Deploys contract artifacts through an alloy provider.
+///
+/// Contracts without a checked-in artifact are compiled with `forge build`
+/// from `contracts/<Contract>.sol` first; that is the only path that needs Foundry.
+pub struct ContractDeployer {
+    rpc_url: String,
+    private_key: String,
+    log: Option<ProcessLog>,
+}
+
+impl ContractDeployer {
+    pub fn new(rpc_url: &str, private_key: &str) -> Self {
+        Self {
+            rpc_url: rpc_url.to_string(),
+            private_key: private_key.to_string(),
+            log: None,
+        }
+    }
+
+    /// Record deployments and any forge build output in `log`
+    pub fn with_log(mut self, log: ProcessLog) -> Self {
+        self.log = Some(log);
+        self
+    }
+
+    /// Deploy `name` with `constructor_args` and wait for it to be mined
+    pub async fn deploy(&self, name: &str, constructor_args: &[DynSolValue]) -> Result<Deployment> {
+        let artifact = match ContractArtifact::load(name)? {
+            Some(artifact) => artifact,
+            None => self.build_with_forge(name).await?,
+        };
+        self.deploy_artifact(&artifact, constructor_args).await
+    }
+
+    pub async fn deploy_artifact(&self, artifact: &ContractArtifact, constructor_args: &[DynSolValue]) -> Result<Deployment> {
+        info!("Deploying {} contract...", artifact.name);
+
+        let signer: PrivateKeySigner = self.private_key.parse()
+            .context("Invalid deployer private key")?;
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(EthereumWallet::from(signer))
+            .on_http(self.rpc_url.parse()?);
+
+        let tx = TransactionRequest::default()
+            .with_deploy_code(artifact.deploy_code(constructor_args)?);
+        let receipt = provider.send_transaction(tx).await
+            .with_context(|| format!("Failed to send {} deployment", artifact.name))?
+            .get_receipt().await
+            .with_context(|| format!("Failed to get {} deployment receipt", artifact.name))?;
+
+        if !receipt.status() {
+            return Err(anyhow::anyhow!(
+                "{} deployment reverted in transaction {}", artifact.name, receipt.transaction_hash
+            ));
+        }
+        let address = receipt.contract_address
+            .ok_or_else(|| anyhow::anyhow!("{} deployment receipt has no contract address", artifact.name))?;
+        let deployment = Deployment {
+            address: address.to_string(),
+            tx_hash: receipt.transaction_hash.to_string(),
+            block_number: receipt.block_number
+                .ok_or_else(|| anyhow::anyhow!("{} deployment receipt has no block number", artifact.name))?,
+            source: artifact.source,
+        };
+
+        if let Some(log) = &self.log {
+            log.push_stdout(&format!(
+                "Deployed {} to {} in transaction {} (block {})",
+                artifact.name, deployment.address, deployment.tx_hash, deployment.block_number
+            ));
+        }
+        info!("{} deployed at: {}", artifact.name, deployment.address);
+        Ok(deployment)
+    }
+
+    /// Compile `contracts/<name>.sol` with forge and load the resulting artifact
+    async fn build_with_forge(&self, name: &str) -> Result<ContractArtifact> {
+        let source = Path::new(SOURCES_DIR).join(format!("{}.sol", name));
+        if !source.exists() {
+            return Err(anyhow::anyhow!(
+                "No artifact at {} and no source at {}", artifact_path(name).display(), source.display()
+            ));
+        }
+        info!("No checked-in artifact for {}, building it with forge", name);
+
+        let log = self.log.clone().unwrap_or_else(|| ProcessLog::new("forge"));
+        let mut cmd = TokioCommand::new("forge");
+        cmd.args(["build", "--out", FORGE_OUT_DIR, "--cache-path", "target/forge-cache"])
+            .arg(&source)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let child = spawn_tracked(&mut cmd, &log)
+            .context("Failed to run forge command")?;
+        let pid = child.id();
+        let output = child.wait_with_output().await
+            .context("Failed to wait for forge")?;
+        if let Some(pid) = pid {
+            untrack_if_empty(pid);
+        }
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            log.push_stdout(line);
+        }
+        for line in String::from_utf8_lossy(&output.stderr).lines() {
+            log.push_stderr(line);
+        }
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!("forge build of {} failed: {}", source.display(), stderr));
+        }
+
+        let built = Path::new(FORGE_OUT_DIR)
+            .join(format!("{}.sol", name))
+            .join(format!("{}.json", name));
+        ContractArtifact::from_file(name, &built, ArtifactSource::Forge)
+    }
+}
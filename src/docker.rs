@@ -1,15 +1,16 @@
 use anyhow::Result;
-use std::net::TcpListener;
 use std::process::Command;
 use tokio::time::{sleep, Duration};
 
+use crate::ports::{allocate_free_port, port_accepting};
+
 pub async fn start_postgres_container() -> Result<(String, u16)> {
     // Ensure Docker daemon is running (try to start it programmatically if not)
     ensure_docker_daemon().await?;
 
     // Pick a free local port
     let port = allocate_free_port()?;
-    let name = format!("rindexer_pg_{}_{port}", std::process::id());
+    let name = format!("{}{port}", run_container_prefix());
 
     // Run container
     let status = Command::new("docker")
@@ -37,7 +38,7 @@ pub async fn start_postgres_container() -> Result<(String, u16)> {
 
     // Wait for port to be ready
     for _ in 0..40 {
-        if port_open(port).await {
+        if port_accepting(port).await {
             return Ok((name, port));
         }
         sleep(Duration::from_millis(250)).await;
@@ -81,20 +82,46 @@ fn which(bin: &str) -> bool {
     Command::new("which").arg(bin).output().map(|o| o.status.success()).unwrap_or(false)
 }
 
+/// Force-remove container `name`; one that is already gone counts as removed
 pub async fn stop_postgres_container(name: &str) -> Result<()> {
-    let _ = Command::new("docker").args(["rm", "-f", name]).output();
+    let out = Command::new("docker").args(["rm", "-f", name]).output()
+        .map_err(|e| anyhow::anyhow!("Failed to run docker rm: {}", e))?;
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if !out.status.success() && !stderr.contains("No such container") {
+        return Err(anyhow::anyhow!("docker rm -f {} failed: {}", name, stderr.trim()));
+    }
     Ok(())
 }
 
-fn allocate_free_port() -> Result<u16> {
-    let listener = TcpListener::bind(("127.0.0.1", 0))?;
-    let port = listener.local_addr()?.port();
-    drop(listener);
-    Ok(port)
+/// Name prefix shared by every Postgres container this process starts
+fn run_container_prefix() -> String {
+    format!("rindexer_pg_{}_", std::process::id())
+}
+
+/// Force-remove every Postgres container this process started, for when a
+/// run is interrupted before the tests' teardown; returns the names removed
+pub fn remove_run_containers() -> Vec<String> {
+    let prefix = run_container_prefix();
+    let names = Command::new("docker")
+        .args(["ps", "-a", "--format", "{{.Names}}", "--filter", &format!("name=^/?{}", prefix)])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().map(str::to_string).collect::<Vec<_>>())
+        .unwrap_or_default();
+    names.into_iter()
+        .filter(|name| name.starts_with(&prefix))
+        .filter(|name| Command::new("docker").args(["rm", "-f", name]).output()
+            .map(|o| o.status.success())
+            .unwrap_or(false))
+        .collect()
 }
 
-async fn port_open(port: u16) -> bool {
-    tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok()
+/// Whether a container named exactly `name` exists, running or not
+pub fn container_exists(name: &str) -> bool {
+    Command::new("docker")
+        .args(["ps", "-aq", "--filter", &format!("name=^/?{}$", name)])
+        .output()
+        .map(|o| o.status.success() && !o.stdout.is_empty())
+        .unwrap_or(false)
 }
 
 
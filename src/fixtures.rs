@@ -0,0 +1,229 @@
+use anyhow::{Result, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use alloy::primitives::Address;
+use alloy::providers::{Provider, ProviderBuilder};
+use tracing::info;
+
+use crate::anvil_setup::{erc20_transfer_request, AnvilInstance, AnvilOptions, Mining, TRANSFER_TOPIC};
+use crate::process_log::ProcessLog;
+
+/// Built fixtures are cached here between runs; they are generated, never checked in
+pub const FIXTURES_DIR: &str = "target/e2e-fixtures";
+
+/// Tests running in parallel share one build of each fixture
+static BUILD_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Transfers that fit in one block at the 100k gas each transaction is sent with
+const MAX_TRANSFERS_PER_BLOCK: u64 = 250;
+
+/// How a fixture chain is generated: SimpleERC20 is deployed, then `blocks`
+/// blocks of `transfers_per_block` transfers each are mined, with `gap`
+/// empty blocks after every one of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureSpec {
+    pub name: String,
+    pub blocks: u64,
+    pub transfers_per_block: u64,
+    pub gap: u64,
+}
+
+impl FixtureSpec {
+    pub fn new(name: &str, blocks: u64, transfers_per_block: u64) -> Self {
+        Self { name: name.to_string(), blocks, transfers_per_block, gap: 0 }
+    }
+
+    pub fn with_gap(mut self, gap: u64) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// The fixtures tests can boot Anvil from with `AnvilOptions::with_fixture`
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            // 20,000 transfers over 4,000 blocks, every other block empty
+            "backfill-20k" => Some(Self::new(name, 2_000, 10).with_gap(1)),
+            _ => None,
+        }
+    }
+}
+
+/// What a built fixture chain contains, written next to its state dump
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureManifest {
+    pub spec: FixtureSpec,
+    /// `anvil --version` of the Anvil that dumped the state
+    pub anvil_version: String,
+    pub chain_id: u64,
+    pub contract_address: String,
+    pub deploy_block: u64,
+    pub head_block: u64,
+    /// Every Transfer on the chain, including the constructor mint
+    pub transfer_events: u64,
+    /// Transfer count of every block that has any
+    pub transfers_by_block: BTreeMap<u64, u64>,
+}
+
+impl FixtureManifest {
+    /// Transfers in blocks `from..=to`
+    pub fn transfers_between(&self, from: u64, to: u64) -> u64 {
+        self.transfers_by_block.range(from..=to).map(|(_, count)| count).sum()
+    }
+}
+
+/// A fixture chain on disk: `<FIXTURES_DIR>/<name>/{state.json,manifest.json}`
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub manifest: FixtureManifest,
+    pub state_path: PathBuf,
+}
+
+impl Fixture {
+    /// The named fixture, building it first unless a build from the same
+    /// spec and Anvil version is cached
+    pub async fn ensure(name: &str) -> Result<Self> {
+        let spec = FixtureSpec::named(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown fixture {}", name))?;
+        let anvil_version = anvil_version()?;
+        let dir = fixture_dir(name);
+        let _build = BUILD_LOCK.lock().await;
+
+        if let Ok(fixture) = Self::load(&dir) {
+            if fixture.manifest.spec == spec && fixture.manifest.anvil_version == anvil_version {
+                return Ok(fixture);
+            }
+            info!("Fixture {} is stale, rebuilding", name);
+        }
+        Self::build(&spec, &anvil_version, &dir).await
+    }
+
+    fn load(dir: &Path) -> Result<Self> {
+        let manifest_path = dir.join("manifest.json");
+        let content = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+        let state_path = dir.join("state.json");
+        if !state_path.exists() {
+            return Err(anyhow::anyhow!("{} has no state dump", dir.display()));
+        }
+        Ok(Self { manifest: serde_json::from_str(&content)?, state_path })
+    }
+
+    async fn build(spec: &FixtureSpec, anvil_version: &str, dir: &Path) -> Result<Self> {
+        if spec.transfers_per_block == 0 || spec.transfers_per_block > MAX_TRANSFERS_PER_BLOCK {
+            return Err(anyhow::anyhow!(
+                "Fixture {} wants {} transfers per block, must be 1..={}",
+                spec.name, spec.transfers_per_block, MAX_TRANSFERS_PER_BLOCK
+            ));
+        }
+        if dir.exists() {
+            std::fs::remove_dir_all(dir)
+                .with_context(|| format!("Failed to clear old fixture at {}", dir.display()))?;
+        }
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create fixture directory {}", dir.display()))?;
+        let state_path = dir.join("state.json");
+        let log = ProcessLog::with_file("anvil", &format!("fixture-{}", spec.name), &dir.join("build.log"))?;
+
+        info!(
+            "Building fixture {}: {} blocks x {} transfers (gap {})",
+            spec.name, spec.blocks, spec.transfers_per_block, spec.gap
+        );
+        let started = Instant::now();
+        let options = AnvilOptions::new()
+            .with_mining(Mining::Auto)
+            .with_dump_state(&state_path);
+        let mut anvil = AnvilInstance::start(options, log.clone()).await?;
+        let result = generate(&anvil, spec, log).await;
+        // Anvil writes the state dump as it exits
+        anvil.stop_gracefully(Duration::from_secs(60)).await?;
+        let mut manifest = result?;
+        manifest.anvil_version = anvil_version.to_string();
+
+        if !state_path.exists() {
+            return Err(anyhow::anyhow!("Anvil exited without writing {}", state_path.display()));
+        }
+        std::fs::write(dir.join("manifest.json"), serde_json::to_string_pretty(&manifest)?)?;
+        info!(
+            "Built fixture {} in {:?}: {} transfers up to block {}",
+            spec.name, started.elapsed(), manifest.transfer_events, manifest.head_block
+        );
+        Ok(Self { manifest, state_path })
+    }
+}
+
+async fn generate(anvil: &AnvilInstance, spec: &FixtureSpec, log: ProcessLog) -> Result<FixtureManifest> {
+    let deployment = anvil.deploy_contract("SimpleERC20", &[], log).await?;
+    let deployer = anvil.deployer().address;
+    let provider = ProviderBuilder::new().on_http(anvil.rpc_url.parse()?);
+    let mut nonce = provider.get_transaction_count(deployer).await?;
+
+    // Blocks are mined only once each batch is in the pool
+    anvil.set_automine(false).await?;
+
+    let mut transfers_by_block = BTreeMap::from([(deployment.block_number, 1)]);
+    let mut recipient = 0u64;
+    for _ in 0..spec.blocks {
+        let requests: Vec<serde_json::Value> = (0..spec.transfers_per_block)
+            .map(|_| {
+                recipient += 1;
+                let mut request = erc20_transfer_request(
+                    &deployer.to_string(),
+                    &deployment.address,
+                    &recipient_address(recipient).to_string(),
+                    1,
+                );
+                request["nonce"] = serde_json::json!(format!("{:#x}", nonce));
+                nonce += 1;
+                request
+            })
+            .collect();
+        anvil.send_unsigned_batch(&requests).await?;
+        anvil.mine_n(1).await?;
+        let block = anvil.get_block_number().await?;
+        transfers_by_block.insert(block, spec.transfers_per_block);
+        if spec.gap > 0 {
+            anvil.mine_n(spec.gap).await?;
+        }
+    }
+
+    let manifest = FixtureManifest {
+        spec: spec.clone(),
+        anvil_version: String::new(),
+        chain_id: anvil.chain_id,
+        contract_address: deployment.address.clone(),
+        deploy_block: deployment.block_number,
+        head_block: anvil.get_block_number().await?,
+        transfer_events: transfers_by_block.values().sum(),
+        transfers_by_block,
+    };
+
+    // Catch transactions that did not make it into their block
+    let on_chain = anvil.count_logs(&deployment.address, TRANSFER_TOPIC).await? as u64;
+    if on_chain != manifest.transfer_events {
+        return Err(anyhow::anyhow!(
+            "Fixture {} has {} Transfer logs on chain, expected {}",
+            spec.name, on_chain, manifest.transfer_events
+        ));
+    }
+    Ok(manifest)
+}
+
+fn fixture_dir(name: &str) -> PathBuf {
+    Path::new(FIXTURES_DIR).join(name)
+}
+
+/// A distinct recipient per transfer, so indexed rows are easy to tell apart
+fn recipient_address(n: u64) -> Address {
+    let mut bytes = [0u8; 20];
+    bytes[0] = 0xfe;
+    bytes[12..].copy_from_slice(&n.to_be_bytes());
+    Address::from(bytes)
+}
+
+fn anvil_version() -> Result<String> {
+    let output = std::process::Command::new("anvil").arg("--version").output()
+        .context("Failed to run anvil --version")?;
+    Ok(String::from_utf8_lossy(&output.stdout).lines().next().unwrap_or_default().to_string())
+}
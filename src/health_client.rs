@@ -61,6 +61,20 @@ impl HealthClient {
         Ok(health)
     }
 
+    /// Fetch the raw `/health` status and body without assuming a schema
+    pub async fn get_health_raw(&self) -> Result<(u16, String)> {
+        let url = format!("{}/health", self.base_url);
+        let response = self.client
+            .get(&url)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+            .context("Failed to send health request")?;
+        let status = response.status().as_u16();
+        let body = response.text().await.context("Failed to read health response")?;
+        Ok((status, body))
+    }
+
     /// Wait until the health endpoint responds with HTTP 200, regardless of body schema
     pub async fn wait_for_up(&self, timeout_seconds: u64) -> Result<()> {
         info!("Waiting for health endpoint HTTP 200 (timeout: {}s)", timeout_seconds);
@@ -1,8 +1,20 @@
+pub mod accounts;
 pub mod anvil_setup;
+pub mod contracts;
 pub mod health_client;
 pub mod rindexer_client;
+pub mod rindexer_cli;
 pub mod test_suite;
 pub mod tests;
 pub mod live_feeder;
 pub mod docker;
+pub mod fixtures;
+pub mod ports;
+pub mod process_group;
+pub mod process_log;
+pub mod artifacts;
+pub mod supervisor;
+pub mod rpc_proxy;
+pub mod cassette;
+pub mod ws_proxy;
 
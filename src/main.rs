@@ -1,17 +1,31 @@
 use clap::Parser;
 use tracing::{info, error};
 use tracing_subscriber::{fmt, EnvFilter};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+mod accounts;
 mod anvil_setup;
+mod contracts;
 mod rindexer_client;
+mod rindexer_cli;
 mod test_suite;
 mod tests;
 mod health_client;
 mod live_feeder;
 mod docker;
+mod fixtures;
+mod ports;
+mod process_group;
+mod process_log;
+mod artifacts;
+mod supervisor;
+mod rpc_proxy;
+mod cassette;
+mod ws_proxy;
 
-use tests::run_tests;
+use tests::{run_tests, RunOptions};
+use tests::registry::{TagExpression, TestRegistry, TestSelection, TestTag};
+use tests::report::ReportSpec;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -27,6 +41,43 @@ struct Args {
     /// Specific tests to run (comma-separated). If not provided, runs all tests.
     #[arg(long)]
     tests: Option<String>,
+
+    /// Only run tests matching a tag expression: comma-separated alternatives, `+` to require several
+    /// tags (e.g. `historic,postgres+live`). Tags: historic, live, postgres, graphql, network, slow.
+    #[arg(long, value_name = "EXPR")]
+    tags: Option<TagExpression>,
+
+    /// Skip tests carrying any of these tags (comma-separated)
+    #[arg(long, value_name = "TAGS", value_delimiter = ',')]
+    exclude_tags: Vec<TestTag>,
+
+    /// List the selected tests with their tags, timeout and description, then exit
+    #[arg(long)]
+    list: bool,
+
+    /// Number of tests to run in parallel. Each parallel test gets its own ports and project dir.
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
+
+    /// Write a machine-readable report (FORMAT=PATH, FORMAT is junit or json). May be repeated.
+    #[arg(long = "report", value_name = "FORMAT=PATH")]
+    reports: Vec<ReportSpec>,
+
+    /// Directory for per-test failure artifact bundles (config, logs, CSVs, health, schema)
+    #[arg(long, value_name = "DIR", default_value = "target/e2e-artifacts")]
+    artifacts_dir: PathBuf,
+
+    /// Keep each test's temporary project directory instead of deleting it on teardown
+    #[arg(long)]
+    keep_temp: bool,
+
+    /// Directory for per-test process logs (one timestamped file per process)
+    #[arg(long, value_name = "DIR", default_value = "target/e2e-logs")]
+    logs_dir: PathBuf,
+
+    /// Echo Anvil and rindexer output to the terminal as it arrives
+    #[arg(short, long)]
+    verbose: bool,
 }
 
 #[tokio::main]
@@ -40,6 +91,17 @@ async fn main() -> anyhow::Result<()> {
 
     let args = Args::parse();
     
+    let selection = TestSelection {
+        names: args.tests.map(|t| t.split(',').map(|s| s.trim().to_string()).collect()),
+        tags: args.tags,
+        exclude_tags: args.exclude_tags,
+    };
+    
+    if args.list {
+        TestRegistry::print_test_list(&selection);
+        return Ok(());
+    }
+    
     // Initialize tracing with configurable log level
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(&args.log_level));
@@ -51,13 +113,23 @@ async fn main() -> anyhow::Result<()> {
         .with_thread_names(true)
         .init();
     
+    process_log::set_echo(args.verbose);
+    
     info!("Starting Rindexer E2E Test Suite");
     info!("Binary: {}", args.rindexer_binary);
     
     // Run the test suite using the new registry-based runner
-    let test_names = args.tests.map(|t| t.split(',').map(|s| s.trim().to_string()).collect());
+    let options = RunOptions {
+        rindexer_binary: args.rindexer_binary,
+        selection,
+        jobs: args.jobs,
+        reports: args.reports,
+        artifacts_dir: Some(args.artifacts_dir),
+        keep_temp: args.keep_temp,
+        logs_dir: Some(args.logs_dir),
+    };
     
-    match run_tests(args.rindexer_binary, test_names).await {
+    match run_tests(options).await {
         Ok(_) => {
             info!("Test suite completed successfully");
         }
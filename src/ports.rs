@@ -0,0 +1,55 @@
+use anyhow::Result;
+use std::collections::HashSet;
+use std::net::TcpListener;
+use std::sync::{Mutex, OnceLock};
+
+/// Ports handed out during this run. The OS may return a port we released a
+/// moment ago, so we remember every allocation to keep parallel tests apart.
+fn reserved_ports() -> &'static Mutex<HashSet<u16>> {
+    static RESERVED: OnceLock<Mutex<HashSet<u16>>> = OnceLock::new();
+    RESERVED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Allocate a free local TCP port that has not been handed out before in this run
+pub fn allocate_free_port() -> Result<u16> {
+    for _ in 0..50 {
+        let listener = TcpListener::bind(("127.0.0.1", 0))?;
+        let port = listener.local_addr()?.port();
+        drop(listener);
+
+        let mut reserved = reserved_ports().lock().unwrap();
+        if reserved.insert(port) {
+            return Ok(port);
+        }
+    }
+    Err(anyhow::anyhow!("Could not allocate a free local port"))
+}
+
+/// Whether something is accepting TCP connections on local `port`
+pub async fn port_accepting(port: u16) -> bool {
+    tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok()
+}
+
+/// Ports used by the services of a single test
+#[derive(Debug, Clone, Copy)]
+pub struct TestPorts {
+    pub anvil: u16,
+    pub health: u16,
+    pub graphql: u16,
+}
+
+impl TestPorts {
+    /// Fixed well-known ports, used when tests run one at a time
+    pub fn fixed(anvil: u16, health: u16, graphql: u16) -> Self {
+        Self { anvil, health, graphql }
+    }
+
+    /// Allocate a fresh set of free ports for an isolated test
+    pub fn allocate() -> Result<Self> {
+        Ok(Self {
+            anvil: allocate_free_port()?,
+            health: allocate_free_port()?,
+            graphql: allocate_free_port()?,
+        })
+    }
+}
@@ -0,0 +1,112 @@
+use anyhow::Result;
+use nix::errno::Errno;
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+
+use crate::process_log::ProcessLog;
+
+/// Send SIGKILL to the whole process group led by `pid`.
+///
+/// Child processes are spawned with `process_group(0)`, so their PID is also
+/// their process group id and this reaches anything they forked as well.
+/// A group that has already exited is not an error.
+pub fn kill_process_group(pid: u32) -> Result<()> {
+    signal_process_group(pid, Signal::SIGKILL)
+}
+
+/// Send `signal` to the whole process group led by `pid`, like a terminal
+/// does for Ctrl-C. A group that has already exited is not an error.
+pub fn signal_process_group(pid: u32, signal: Signal) -> Result<()> {
+    match killpg(Pid::from_raw(pid as i32), signal) {
+        Ok(()) | Err(Errno::ESRCH) => Ok(()),
+        Err(e) => Err(anyhow::anyhow!("Failed to send {} to process group {}: {}", signal, pid, e)),
+    }
+}
+
+/// A process group the harness started and has not yet seen become empty
+#[derive(Debug, Clone)]
+pub struct TrackedGroup {
+    pub pgid: u32,
+    pub name: String,
+    /// Test that started the group, if it was started for one
+    pub owner: Option<String>,
+}
+
+impl std::fmt::Display for TrackedGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (process group {})", self.name, self.pgid)
+    }
+}
+
+fn tracked_groups() -> &'static Mutex<HashMap<u32, TrackedGroup>> {
+    static TRACKED: OnceLock<Mutex<HashMap<u32, TrackedGroup>>> = OnceLock::new();
+    TRACKED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Spawn `cmd` as the leader of a new process group and track the group
+/// until it is empty. `log` names the process and ties it to its test.
+///
+/// Only groups started here are ever signalled by the harness, so processes
+/// a developer has running for other work are never touched.
+pub fn spawn_tracked(cmd: &mut Command, log: &ProcessLog) -> std::io::Result<Child> {
+    cmd.process_group(0).kill_on_drop(true);
+    let child = cmd.spawn()?;
+    if let Some(pgid) = child.id() {
+        tracked_groups().lock().unwrap().insert(pgid, TrackedGroup {
+            pgid,
+            name: log.name().to_string(),
+            owner: log.owner().map(str::to_string),
+        });
+    }
+    Ok(child)
+}
+
+/// Whether any process (including an unreaped zombie) is left in the group led by `pgid`
+pub fn process_group_alive(pgid: u32) -> bool {
+    !matches!(killpg(Pid::from_raw(pgid as i32), None), Err(Errno::ESRCH))
+}
+
+/// Stop tracking `pgid` if nothing is left in its group. Called once the
+/// leader has been reaped; survivors stay tracked and show up as leaks.
+pub fn untrack_if_empty(pgid: u32) {
+    if !process_group_alive(pgid) {
+        tracked_groups().lock().unwrap().remove(&pgid);
+    }
+}
+
+/// Groups started for `owner` that still have live processes after `grace`.
+/// They are killed and forgotten; empty groups are forgotten silently.
+pub async fn reap_leaked_groups(owner: &str, grace: Duration) -> Vec<TrackedGroup> {
+    let deadline = Instant::now() + grace;
+    loop {
+        let alive: Vec<TrackedGroup> = {
+            let mut tracked = tracked_groups().lock().unwrap();
+            tracked.retain(|pgid, group| group.owner.as_deref() != Some(owner) || process_group_alive(*pgid));
+            tracked.values().filter(|g| g.owner.as_deref() == Some(owner)).cloned().collect()
+        };
+        // Killed children may linger as zombies for a moment until they are reaped
+        if alive.is_empty() || Instant::now() >= deadline {
+            let mut tracked = tracked_groups().lock().unwrap();
+            for group in &alive {
+                let _ = kill_process_group(group.pgid);
+                tracked.remove(&group.pgid);
+            }
+            return alive;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Kill every process group still tracked, whichever test started it.
+/// Run when the runner exits, including on Ctrl-C.
+pub fn reap_all_groups() -> Vec<TrackedGroup> {
+    let groups: Vec<TrackedGroup> = tracked_groups().lock().unwrap().drain().map(|(_, g)| g).collect();
+    groups.into_iter()
+        .filter(|group| process_group_alive(group.pgid))
+        .inspect(|group| { let _ = kill_process_group(group.pgid); })
+        .collect()
+}
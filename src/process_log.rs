@@ -0,0 +1,221 @@
+use anyhow::{Result, Context};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Lines kept in memory per process; older lines are only in the log file
+const RING_CAPACITY: usize = 10_000;
+
+static ECHO: AtomicBool = AtomicBool::new(false);
+
+/// Echo every captured line to the terminal (verbose mode)
+pub fn set_echo(enabled: bool) {
+    ECHO.store(enabled, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub timestamp: SystemTime,
+    pub stream: LogStream,
+    pub text: String,
+}
+
+impl std::fmt::Display for LogLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}] {}", format_timestamp(self.timestamp), self.stream.as_str(), self.text)
+    }
+}
+
+/// UTC wall-clock time of day with millisecond precision, e.g. `14:03:27.512`
+fn format_timestamp(timestamp: SystemTime) -> String {
+    let since_epoch = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs() % 86_400;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        secs / 3600,
+        (secs / 60) % 60,
+        secs % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Captured output of a single child process (Anvil, rindexer, ...).
+///
+/// Every line is timestamped, kept in an in-memory ring buffer that tests can
+/// query, and appended to the process's log file when one is attached.
+#[derive(Debug, Clone)]
+pub struct ProcessLog {
+    name: String,
+    /// Prefix used when echoing to the terminal, e.g. `test_6_postgres/rindexer`
+    label: String,
+    /// Test the process was started for
+    owner: Option<String>,
+    inner: Arc<Mutex<CapturedOutput>>,
+}
+
+#[derive(Debug, Default)]
+struct CapturedOutput {
+    lines: VecDeque<LogLine>,
+    file: Option<LineWriter<File>>,
+    path: Option<PathBuf>,
+}
+
+impl ProcessLog {
+    /// An in-memory log with no file behind it
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            label: name.to_string(),
+            owner: None,
+            inner: Arc::new(Mutex::new(CapturedOutput::default())),
+        }
+    }
+
+    /// A log that also writes every line to `path`
+    pub fn with_file(name: &str, label: &str, path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create log file {}", path.display()))?;
+        let log = Self {
+            name: name.to_string(),
+            label: label.to_string(),
+            owner: None,
+            inner: Arc::new(Mutex::new(CapturedOutput::default())),
+        };
+        {
+            let mut inner = log.inner.lock().unwrap();
+            inner.file = Some(LineWriter::new(file));
+            inner.path = Some(path.to_path_buf());
+        }
+        Ok(log)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Test the process was started for, when the log belongs to one
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    /// The file this log is written to, if any
+    pub fn path(&self) -> Option<PathBuf> {
+        self.inner.lock().unwrap().path.clone()
+    }
+
+    pub fn push_stdout(&self, line: &str) {
+        self.push(LogStream::Stdout, line);
+    }
+
+    pub fn push_stderr(&self, line: &str) {
+        self.push(LogStream::Stderr, line);
+    }
+
+    fn push(&self, stream: LogStream, text: &str) {
+        let line = LogLine { timestamp: SystemTime::now(), stream, text: text.to_string() };
+
+        if ECHO.load(Ordering::Relaxed) {
+            match stream {
+                LogStream::Stdout => println!("[{}] {}", self.label, text),
+                LogStream::Stderr => eprintln!("[{}] {}", self.label, text),
+            }
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(file) = inner.file.as_mut() {
+            // A full disk should not take the test down with it
+            let _ = writeln!(file, "{}", line);
+        }
+        if inner.lines.len() == RING_CAPACITY {
+            inner.lines.pop_front();
+        }
+        inner.lines.push_back(line);
+    }
+
+    /// Every line still held in memory, oldest first
+    pub fn lines(&self) -> Vec<LogLine> {
+        self.inner.lock().unwrap().lines.iter().cloned().collect()
+    }
+
+    /// The last `n` lines held in memory
+    pub fn tail(&self, n: usize) -> Vec<LogLine> {
+        let inner = self.inner.lock().unwrap();
+        inner.lines.iter().skip(inner.lines.len().saturating_sub(n)).cloned().collect()
+    }
+}
+
+/// Every process log created for one test, in creation order
+#[derive(Debug, Clone, Default)]
+pub struct ProcessLogs {
+    logs: Arc<Mutex<Vec<ProcessLog>>>,
+    /// Directory holding one `<process>.log` file per process; in-memory only when unset
+    dir: Option<PathBuf>,
+    label: Option<String>,
+}
+
+impl ProcessLogs {
+    /// Logs written under `dir` (cleared first), with terminal echo prefixed by `label`
+    pub fn in_dir(label: &str, dir: &Path) -> Result<Self> {
+        if dir.exists() {
+            std::fs::remove_dir_all(dir)
+                .with_context(|| format!("Failed to clear old logs at {}", dir.display()))?;
+        }
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+        Ok(Self {
+            logs: Arc::default(),
+            dir: Some(dir.to_path_buf()),
+            label: Some(label.to_string()),
+        })
+    }
+
+    /// Create and register a log. Repeated names get a numeric suffix
+    /// (`rindexer`, `rindexer-2`, ...) so restarts keep separate logs.
+    pub fn create(&self, name: &str) -> ProcessLog {
+        let mut logs = self.logs.lock().unwrap();
+        let mut unique = name.to_string();
+        let mut n = 1;
+        while logs.iter().any(|l| l.name == unique) {
+            n += 1;
+            unique = format!("{}-{}", name, n);
+        }
+        let label = match &self.label {
+            Some(label) => format!("{}/{}", label, unique),
+            None => unique.clone(),
+        };
+        let mut log = match &self.dir {
+            Some(dir) => ProcessLog::with_file(&unique, &label, &dir.join(format!("{}.log", unique)))
+                .unwrap_or_else(|e| {
+                    tracing::warn!("{:#}, keeping {} output in memory only", e, unique);
+                    ProcessLog::new(&unique)
+                }),
+            None => ProcessLog::new(&unique),
+        };
+        log.owner = self.label.clone();
+        logs.push(log.clone());
+        log
+    }
+
+    pub fn all(&self) -> Vec<ProcessLog> {
+        self.logs.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,308 @@
+use anyhow::{Result, Context};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command as TokioCommand;
+use tracing::info;
+
+use crate::process_group::{kill_process_group, spawn_tracked, untrack_if_empty};
+use crate::process_log::{ProcessLog, ProcessLogs};
+use crate::rindexer_client::{postgres_env, resolve_binary_path, RindexerInstance};
+use crate::supervisor::CrashMonitor;
+
+const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Storage answer to the `rindexer new` prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageChoice {
+    Postgres,
+    Csv,
+    Both,
+    None,
+}
+
+impl StorageChoice {
+    fn as_answer(&self) -> &'static str {
+        match self {
+            StorageChoice::Postgres => "postgres",
+            StorageChoice::Csv => "csv",
+            StorageChoice::Both => "both",
+            StorageChoice::None => "none",
+        }
+    }
+
+    pub fn has_postgres(&self) -> bool {
+        matches!(self, StorageChoice::Postgres | StorageChoice::Both)
+    }
+
+    pub fn has_csv(&self) -> bool {
+        matches!(self, StorageChoice::Csv | StorageChoice::Both)
+    }
+}
+
+/// Answers for `rindexer new no-code`
+#[derive(Debug, Clone)]
+pub struct NewProjectOptions {
+    pub name: String,
+    pub description: String,
+    pub repository: String,
+    pub storage: StorageChoice,
+    /// Only asked when Postgres is enabled
+    pub postgres_docker: bool,
+}
+
+impl NewProjectOptions {
+    pub fn new(name: &str, storage: StorageChoice) -> Self {
+        Self {
+            name: name.to_string(),
+            description: String::new(),
+            repository: String::new(),
+            storage,
+            postgres_docker: false,
+        }
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    pub fn with_postgres_docker(mut self, postgres_docker: bool) -> Self {
+        self.postgres_docker = postgres_docker;
+        self
+    }
+
+    /// Prompt answers in the order `rindexer new` asks them
+    fn answers(&self) -> Vec<String> {
+        let mut answers = vec![
+            self.name.clone(),
+            self.description.clone(),
+            self.repository.clone(),
+            self.storage.as_answer().to_string(),
+        ];
+        if self.storage.has_postgres() {
+            answers.push(if self.postgres_docker { "yes" } else { "no" }.to_string());
+        }
+        answers
+    }
+}
+
+/// What `rindexer codegen` should generate. Rust-project targets (`typings`,
+/// `indexer`) need a Rust project and go through `RindexerCli::command`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodegenTarget {
+    Graphql { endpoint: String },
+}
+
+/// Result of a finished CLI invocation
+#[derive(Debug, Clone)]
+pub struct CliOutput {
+    pub args: Vec<String>,
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CliOutput {
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+
+    /// Fail with the command's output unless it exited zero
+    pub fn expect_success(self) -> Result<Self> {
+        if self.success() {
+            Ok(self)
+        } else {
+            Err(anyhow::anyhow!(
+                "`rindexer {}` failed with {}\nstdout:\n{}\nstderr:\n{}",
+                self.args.join(" "), self.status, self.stdout, self.stderr
+            ))
+        }
+    }
+}
+
+/// Typed access to rindexer's CLI subcommands.
+///
+/// Short-lived commands return a `CliOutput`; `start graphql` returns a
+/// supervised `RindexerInstance` like `start indexer` does.
+#[derive(Debug, Clone)]
+pub struct RindexerCli {
+    binary_path: String,
+    env: HashMap<String, String>,
+    timeout: Duration,
+    logs: Option<ProcessLogs>,
+    crash_monitor: Option<CrashMonitor>,
+}
+
+impl RindexerCli {
+    pub fn new(binary_path: &str) -> Self {
+        Self {
+            binary_path: binary_path.to_string(),
+            env: HashMap::new(),
+            timeout: DEFAULT_COMMAND_TIMEOUT,
+            logs: None,
+            crash_monitor: None,
+        }
+    }
+
+    /// Point commands at a test Postgres container listening on `pg_port`
+    pub fn with_postgres(mut self, pg_port: u16) -> Self {
+        self.env.extend(postgres_env(pg_port));
+        self
+    }
+
+    /// Record each command's output as `cli-<subcommand>` in `logs`
+    pub fn with_logs(mut self, logs: ProcessLogs) -> Self {
+        self.logs = Some(logs);
+        self
+    }
+
+    /// Report crashes of long-running services started through this CLI to `monitor`
+    pub fn with_crash_monitor(mut self, monitor: CrashMonitor) -> Self {
+        self.crash_monitor = Some(monitor);
+        self
+    }
+
+    /// An arbitrary subcommand run in `cwd`
+    pub fn command(&self, cwd: &Path) -> CliCommand<'_> {
+        CliCommand { cli: self, cwd: cwd.to_path_buf(), args: Vec::new(), answers: Vec::new() }
+    }
+
+    /// `rindexer new no-code`, creating `<parent_dir>/<name>`
+    pub fn new_no_code(&self, parent_dir: &Path, options: &NewProjectOptions) -> CliCommand<'_> {
+        self.command(parent_dir)
+            .args(&["new", "no-code"])
+            .answers(&options.answers())
+    }
+
+    /// `rindexer add contract`, answering the network and address prompts
+    pub fn add_contract(&self, project: &Path, network: &str, address: &str) -> CliCommand<'_> {
+        self.command(project)
+            .args(&["add", "contract"])
+            .answers(&[network.to_string(), address.to_string()])
+    }
+
+    /// `rindexer codegen <target>`
+    pub fn codegen(&self, project: &Path, target: &CodegenTarget) -> CliCommand<'_> {
+        let command = self.command(project).args(&["codegen"]);
+        match target {
+            CodegenTarget::Graphql { endpoint } => command.args(&["graphql", "--endpoint", endpoint]),
+        }
+    }
+
+    /// `rindexer delete`, confirming every prompt
+    pub fn delete(&self, project: &Path) -> CliCommand<'_> {
+        self.command(project)
+            .args(&["delete"])
+            .answers(&["yes".to_string(), "yes".to_string()])
+    }
+
+    /// `rindexer start graphql` on `port`, supervised like the indexer
+    pub async fn start_graphql(&self, project: &Path, port: u16) -> Result<RindexerInstance> {
+        let mut instance = RindexerInstance::new(&self.binary_path, project.to_path_buf())
+            .with_env("PORT", &port.to_string());
+        for (key, value) in &self.env {
+            instance = instance.with_env(key, value);
+        }
+        if let Some(logs) = &self.logs {
+            instance = instance.with_log(logs.create("rindexer-graphql"));
+        }
+        if let Some(monitor) = &self.crash_monitor {
+            instance = instance.with_crash_monitor(monitor.clone());
+        }
+        instance.start_graphql().await?;
+        Ok(instance)
+    }
+}
+
+/// One CLI invocation being built
+pub struct CliCommand<'a> {
+    cli: &'a RindexerCli,
+    cwd: PathBuf,
+    args: Vec<String>,
+    answers: Vec<String>,
+}
+
+impl CliCommand<'_> {
+    pub fn args(mut self, args: &[&str]) -> Self {
+        self.args.extend(args.iter().map(|a| a.to_string()));
+        self
+    }
+
+    /// Lines written to stdin, one per interactive prompt
+    pub fn answers(mut self, answers: &[String]) -> Self {
+        self.answers.extend(answers.iter().cloned());
+        self
+    }
+
+    /// Run to completion, capturing output. A non-zero exit is not an error
+    /// here; use `CliOutput::expect_success` for that.
+    pub async fn run(self) -> Result<CliOutput> {
+        let binary_path = resolve_binary_path(&self.cli.binary_path)?;
+        info!("Running rindexer {} in {}", self.args.join(" "), self.cwd.display());
+
+        let name = format!("cli-{}", self.args.first().map(String::as_str).unwrap_or("rindexer"));
+        let log = match &self.cli.logs {
+            Some(logs) => logs.create(&name),
+            None => ProcessLog::new(&name),
+        };
+
+        let mut cmd = TokioCommand::new(&binary_path);
+        cmd.current_dir(&self.cwd)
+           .args(&self.args)
+           .envs(&self.cli.env)
+           .stdin(Stdio::piped())
+           .stdout(Stdio::piped())
+           .stderr(Stdio::piped());
+
+        let mut child = spawn_tracked(&mut cmd, &log)
+            .with_context(|| format!("Failed to run rindexer {}", self.args.join(" ")))?;
+        let pid = child.id();
+
+        if let Some(mut stdin) = child.stdin.take() {
+            let mut input = self.answers.join("\n");
+            input.push('\n');
+            // The command may exit without reading every answer
+            let _ = stdin.write_all(input.as_bytes()).await;
+        }
+
+        let output = match tokio::time::timeout(self.cli.timeout, child.wait_with_output()).await {
+            Ok(output) => {
+                if let Some(pid) = pid {
+                    untrack_if_empty(pid);
+                }
+                output?
+            }
+            Err(_) => {
+                if let Some(pid) = pid {
+                    let _ = kill_process_group(pid);
+                }
+                return Err(anyhow::anyhow!(
+                    "`rindexer {}` did not finish within {:?}",
+                    self.args.join(" "), self.cli.timeout
+                ));
+            }
+        };
+
+        let result = CliOutput {
+            args: self.args.clone(),
+            status: output.status,
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        };
+        record(&log, &result);
+        info!("rindexer {} exited with {}", self.args.join(" "), result.status);
+        Ok(result)
+    }
+}
+
+fn record(log: &ProcessLog, output: &CliOutput) {
+    for line in output.stdout.lines() {
+        log.push_stdout(line);
+    }
+    for line in output.stderr.lines() {
+        log.push_stderr(line);
+    }
+}
@@ -0,0 +1,615 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::AbortHandle;
+use tracing::info;
+
+use crate::cassette::{Cassette, Replay};
+use crate::ports::allocate_free_port;
+use crate::process_log::ProcessLog;
+
+/// Something that goes wrong with a JSON-RPC call on its way through the proxy
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    /// Forward the call after waiting this long
+    Latency(Duration),
+    /// Answer with this HTTP status (500, 429, ...) instead of forwarding
+    HttpStatus(u16),
+    /// Answer every request in the call with this JSON-RPC error instead of forwarding
+    JsonRpcError { code: i64, message: String },
+    /// Close the connection without answering
+    DropConnection,
+    /// Forward the call, then close the connection halfway through the response body
+    TruncateBody,
+}
+
+/// A fault and the calls it applies to
+#[derive(Debug, Clone)]
+pub struct FaultRule {
+    pub fault: Fault,
+    /// Only calls to this method (any request of a batch); every call when `None`
+    pub method: Option<String>,
+    /// Matching calls left to fault; every matching call when `None`
+    pub remaining: Option<u32>,
+}
+
+impl FaultRule {
+    pub fn new(fault: Fault) -> Self {
+        Self { fault, method: None, remaining: None }
+    }
+
+    pub fn for_method(mut self, method: &str) -> Self {
+        self.method = Some(method.to_string());
+        self
+    }
+
+    /// Fault only the next `n` matching calls
+    pub fn times(mut self, n: u32) -> Self {
+        self.remaining = Some(n);
+        self
+    }
+
+    fn matches(&self, methods: &[String]) -> bool {
+        self.remaining != Some(0)
+            && self.method.as_ref().is_none_or(|method| methods.contains(method))
+    }
+}
+
+/// `eth_getLogs` limits of hosted RPC providers, enforced on single (non-batch)
+/// requests the way those providers reject them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogsLimit {
+    /// Reject filters spanning more blocks than this
+    /// ("eth_getLogs is limited to a 1000 range")
+    BlockSpan(u64),
+    /// Reject calls that would return more logs than this, without saying
+    /// which range would fit
+    Results(usize),
+    /// Reject calls that would return more logs than this, suggesting the
+    /// widest range from the same start that fits
+    /// ("query returned more than 10000 results. Try with this block range [0x.., 0x..].")
+    ResultsWithSuggestedRange(usize),
+}
+
+/// What the proxy has seen so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProxyStats {
+    /// HTTP requests received, including faulted ones
+    pub requests: u64,
+    /// Requests answered by a fault rule
+    pub faults: u64,
+    /// Requests and connections dropped while partitioned
+    pub partitioned: u64,
+    /// `eth_getLogs` calls rejected by the `LogsLimit`
+    pub limited: u64,
+    /// Replayed requests the cassette had no answer for
+    pub unrecorded: u64,
+}
+
+/// A JSON-RPC request as issued to the proxy, recorded whatever happened to it
+#[derive(Debug, Clone)]
+pub struct RpcCall {
+    pub method: String,
+    pub params: serde_json::Value,
+    /// `fromBlock` of an `eth_getLogs` filter, when it is a number or `earliest`
+    pub from_block: Option<u64>,
+    /// `toBlock` of an `eth_getLogs` filter, when it is a number or `earliest`
+    pub to_block: Option<u64>,
+}
+
+impl RpcCall {
+    fn from_request(request: &serde_json::Value) -> Option<Self> {
+        let method = request["method"].as_str()?.to_string();
+        let params = request["params"].clone();
+        let (from_block, to_block) = if method == "eth_getLogs" {
+            (block_tag(&params[0]["fromBlock"]), block_tag(&params[0]["toBlock"]))
+        } else {
+            (None, None)
+        };
+        Some(Self { method, params, from_block, to_block })
+    }
+
+    /// Blocks an `eth_getLogs` call with numeric bounds asked for
+    pub fn block_range(&self) -> Option<RangeInclusive<u64>> {
+        Some(self.from_block?..=self.to_block?)
+    }
+}
+
+/// Block number of a quantity or `earliest`; `None` for `latest`, `pending`, ...
+fn block_tag(value: &serde_json::Value) -> Option<u64> {
+    match value.as_str()? {
+        "earliest" => Some(0),
+        hex => u64::from_str_radix(hex.strip_prefix("0x")?, 16).ok(),
+    }
+}
+
+/// Where the proxy gets its answers from
+enum Upstream {
+    /// A JSON-RPC endpoint over HTTP
+    Http(String),
+    /// A recorded cassette; nothing leaves the machine
+    Replay(Mutex<Replay>),
+}
+
+/// Traffic being recorded into a cassette file
+struct Recording {
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+struct ProxyState {
+    upstream: Upstream,
+    recording: Option<Recording>,
+    /// Checked in order; the first matching rule applies
+    rules: Mutex<Vec<FaultRule>>,
+    partitioned: AtomicBool,
+    logs_limit: Mutex<Option<LogsLimit>>,
+    stats: Mutex<ProxyStats>,
+    /// Every call since the proxy started or `take_calls` was last called
+    calls: Mutex<Vec<RpcCall>>,
+    /// Every connection task, aborted on `stop`
+    tasks: Mutex<Vec<AbortHandle>>,
+    client: reqwest::Client,
+    log: ProcessLog,
+}
+
+/// In-process HTTP JSON-RPC proxy that forwards to `upstream` (or replays a
+/// cassette), records every call and injects scripted faults. Clones share the
+/// same proxy.
+#[derive(Clone)]
+pub struct RpcProxy {
+    /// Point rindexer here instead of at the upstream
+    pub url: String,
+    state: Arc<ProxyState>,
+}
+
+impl RpcProxy {
+    /// Listen on a free local port and forward to `upstream`, recording faults in `log`
+    pub async fn start(upstream: &str, log: ProcessLog) -> Result<Self> {
+        Self::listen(Upstream::Http(upstream.to_string()), None, upstream, log).await
+    }
+
+    /// Like `start`, also capturing every request and its answer for
+    /// `save_cassette` to write to `cassette`
+    pub async fn record(upstream: &str, cassette: &Path, log: ProcessLog) -> Result<Self> {
+        let recording = Recording { path: cassette.to_path_buf(), cassette: Mutex::new(Cassette::default()) };
+        Self::listen(Upstream::Http(upstream.to_string()), Some(recording), upstream, log).await
+    }
+
+    /// Listen on a free local port and answer from `cassette` alone; requests it
+    /// has no answer for get a JSON-RPC error
+    pub async fn replay(cassette: &Path, log: ProcessLog) -> Result<Self> {
+        let replay = Replay::new(Cassette::load(cassette)?);
+        let description = format!("cassette {}", cassette.display());
+        Self::listen(Upstream::Replay(Mutex::new(replay)), None, &description, log).await
+    }
+
+    async fn listen(upstream: Upstream, recording: Option<Recording>, description: &str, log: ProcessLog) -> Result<Self> {
+        let port = allocate_free_port()?;
+        let listener = TcpListener::bind(("127.0.0.1", port)).await
+            .with_context(|| format!("Failed to bind RPC proxy on port {}", port))?;
+        let state = Arc::new(ProxyState {
+            upstream,
+            recording,
+            rules: Mutex::new(Vec::new()),
+            partitioned: AtomicBool::new(false),
+            logs_limit: Mutex::new(None),
+            stats: Mutex::new(ProxyStats::default()),
+            calls: Mutex::new(Vec::new()),
+            tasks: Mutex::new(Vec::new()),
+            client: reqwest::Client::new(),
+            log,
+        });
+
+        let accept_state = state.clone();
+        let accept = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = accept_state.clone();
+                let task = tokio::spawn(async move {
+                    // Connection errors only mean the client went away
+                    let _ = serve_connection(stream, state).await;
+                });
+                let mut tasks = accept_state.tasks.lock().unwrap();
+                tasks.retain(|task| !task.is_finished());
+                tasks.push(task.abort_handle());
+            }
+        });
+        state.tasks.lock().unwrap().push(accept.abort_handle());
+
+        let url = format!("http://127.0.0.1:{}", port);
+        info!("RPC proxy {} -> {}", url, description);
+        state.log.push_stdout(&format!("proxying {} -> {}", url, description));
+        Ok(Self { url, state })
+    }
+
+    /// Add a fault rule after the existing ones
+    pub fn inject(&self, rule: FaultRule) {
+        self.state.log.push_stdout(&format!("inject {:?}", rule));
+        self.state.rules.lock().unwrap().push(rule);
+    }
+
+    /// Remove every fault rule; calls are forwarded untouched again
+    pub fn clear_faults(&self) {
+        self.state.log.push_stdout("clear faults");
+        self.state.rules.lock().unwrap().clear();
+    }
+
+    /// Enforce a provider's `eth_getLogs` limit from now on; `None` lifts it
+    pub fn limit_get_logs(&self, limit: Option<LogsLimit>) {
+        self.state.log.push_stdout(&format!("eth_getLogs limit {:?}", limit));
+        *self.state.logs_limit.lock().unwrap() = limit;
+    }
+
+    /// Drop every connection and request until `heal` is called
+    pub fn partition(&self) {
+        info!("RPC proxy {} partitioned", self.url);
+        self.state.log.push_stdout("partition");
+        self.state.partitioned.store(true, Ordering::SeqCst);
+    }
+
+    pub fn heal(&self) {
+        info!("RPC proxy {} healed", self.url);
+        self.state.log.push_stdout("heal");
+        self.state.partitioned.store(false, Ordering::SeqCst);
+    }
+
+    pub fn stats(&self) -> ProxyStats {
+        *self.state.stats.lock().unwrap()
+    }
+
+    /// Every call recorded so far, in arrival order
+    pub fn calls(&self) -> Vec<RpcCall> {
+        self.state.calls.lock().unwrap().clone()
+    }
+
+    /// Every call recorded so far; the next `calls` starts from scratch
+    pub fn take_calls(&self) -> Vec<RpcCall> {
+        std::mem::take(&mut *self.state.calls.lock().unwrap())
+    }
+
+    /// Number of recorded calls per method
+    pub fn call_counts(&self) -> BTreeMap<String, u64> {
+        let mut counts = BTreeMap::new();
+        for call in self.state.calls.lock().unwrap().iter() {
+            *counts.entry(call.method.clone()).or_default() += 1;
+        }
+        counts
+    }
+
+    pub fn count(&self, method: &str) -> u64 {
+        self.state.calls.lock().unwrap().iter().filter(|call| call.method == method).count() as u64
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.state.recording.is_some()
+    }
+
+    /// Write everything recorded so far to the cassette given to `record`
+    pub fn save_cassette(&self) -> Result<()> {
+        let recording = self.state.recording.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("RPC proxy {} is not recording", self.url))?;
+        let cassette = recording.cassette.lock().unwrap();
+        cassette.save(&recording.path)?;
+        info!("Recorded {} RPC interactions to {:?}", cassette.interactions.len(), recording.path);
+        Ok(())
+    }
+
+    /// Stop listening and close every open connection
+    pub fn stop(&self) {
+        for task in self.state.tasks.lock().unwrap().drain(..) {
+            task.abort();
+        }
+    }
+}
+
+/// One HTTP request as read off a client connection
+struct HttpRequest {
+    body: Vec<u8>,
+    /// The client asked for the connection to be closed after the response
+    close: bool,
+}
+
+async fn serve_connection(stream: TcpStream, state: Arc<ProxyState>) -> Result<()> {
+    if state.partitioned.load(Ordering::SeqCst) {
+        state.stats.lock().unwrap().partitioned += 1;
+        return Ok(());
+    }
+    let (read, mut write) = stream.into_split();
+    let mut reader = BufReader::new(read);
+
+    while let Some(request) = read_request(&mut reader).await? {
+        state.stats.lock().unwrap().requests += 1;
+        let calls = rpc_calls(&request.body);
+        for call in &calls {
+            state.log.push_stdout(&format!("{} {}", call.method, call.params));
+        }
+        state.calls.lock().unwrap().extend(calls.iter().cloned());
+        if state.partitioned.load(Ordering::SeqCst) {
+            state.stats.lock().unwrap().partitioned += 1;
+            return Ok(());
+        }
+
+        let methods: Vec<String> = calls.into_iter().map(|call| call.method).collect();
+        let fault = next_fault(&state, &methods);
+        if let Some(fault) = &fault {
+            state.stats.lock().unwrap().faults += 1;
+            state.log.push_stdout(&format!("{} -> {:?}", methods.join(","), fault));
+        }
+
+        match fault {
+            Some(Fault::DropConnection) => return Ok(()),
+            Some(Fault::HttpStatus(status)) => {
+                write_response(&mut write, status, b"injected fault", None).await?;
+            }
+            Some(Fault::JsonRpcError { code, message }) => {
+                let body = rpc_error_body(&request.body, code, &message);
+                write_response(&mut write, 200, body.as_bytes(), None).await?;
+            }
+            Some(Fault::Latency(delay)) => {
+                tokio::time::sleep(delay).await;
+                let (status, body) = forward_within_limits(&state, request.body).await;
+                write_response(&mut write, status, &body, None).await?;
+            }
+            Some(Fault::TruncateBody) => {
+                let (status, body) = forward_within_limits(&state, request.body).await;
+                write_response(&mut write, status, &body, Some(body.len() / 2)).await?;
+                return Ok(());
+            }
+            None => {
+                let (status, body) = forward_within_limits(&state, request.body).await;
+                write_response(&mut write, status, &body, None).await?;
+            }
+        }
+        if request.close {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+/// Read the next request, or `None` once the client closes the connection
+async fn read_request<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> Result<Option<HttpRequest>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+
+    let mut content_length = 0usize;
+    let mut close = false;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.parse().context("Invalid Content-Length")?,
+                "connection" => close = value.eq_ignore_ascii_case("close"),
+                "transfer-encoding" => {
+                    return Err(anyhow::anyhow!("RPC proxy does not support Transfer-Encoding: {}", value));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(HttpRequest { body, close }))
+}
+
+/// Every request of a JSON-RPC request or batch
+fn rpc_calls(body: &[u8]) -> Vec<RpcCall> {
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(serde_json::Value::Array(batch)) => batch.iter().filter_map(RpcCall::from_request).collect(),
+        Ok(request) => RpcCall::from_request(&request).into_iter().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Blocks covered by the `eth_getLogs` calls in `calls`, as sorted, merged ranges
+pub fn get_logs_coverage(calls: &[RpcCall]) -> Vec<RangeInclusive<u64>> {
+    let mut ranges: Vec<RangeInclusive<u64>> = calls.iter().filter_map(RpcCall::block_range).collect();
+    ranges.sort_by_key(|range| *range.start());
+    let mut merged: Vec<RangeInclusive<u64>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                *last = *last.start()..=(*last.end()).max(*range.end());
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// The fault of the first rule matching `methods`, using up one of its calls
+fn next_fault(state: &ProxyState, methods: &[String]) -> Option<Fault> {
+    let mut rules = state.rules.lock().unwrap();
+    let rule = rules.iter_mut().find(|rule| rule.matches(methods))?;
+    if let Some(remaining) = rule.remaining.as_mut() {
+        *remaining -= 1;
+    }
+    Some(rule.fault.clone())
+}
+
+/// A JSON-RPC error response for every request in `body`, keeping their ids
+fn rpc_error_body(body: &[u8], code: i64, message: &str) -> String {
+    let error = |request: &serde_json::Value| serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request["id"],
+        "error": { "code": code, "message": message }
+    });
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(serde_json::Value::Array(batch)) => {
+            serde_json::Value::Array(batch.iter().map(error).collect()).to_string()
+        }
+        Ok(request) => error(&request).to_string(),
+        Err(_) => error(&serde_json::Value::Null).to_string(),
+    }
+}
+
+/// Forward `body`, unless it is an `eth_getLogs` call the current `LogsLimit`
+/// rejects; that is answered with the provider's JSON-RPC error instead
+async fn forward_within_limits(state: &ProxyState, body: Vec<u8>) -> (u16, Vec<u8>) {
+    let Some(limit) = *state.logs_limit.lock().unwrap() else {
+        return forward(state, body).await;
+    };
+    let request = match serde_json::from_slice::<serde_json::Value>(&body) {
+        Ok(request) if request["method"] == "eth_getLogs" => request,
+        _ => return forward(state, body).await,
+    };
+    let filter = &request["params"][0];
+
+    if let LogsLimit::BlockSpan(max) = limit {
+        let Some(from) = block_tag(&filter["fromBlock"]) else {
+            return forward(state, body).await;
+        };
+        let to = match block_tag(&filter["toBlock"]) {
+            Some(to) => Some(to),
+            None => upstream_head(state).await,
+        };
+        if to.is_some_and(|to| to.saturating_sub(from) + 1 > max) {
+            let message = format!("eth_getLogs is limited to a {} range", max);
+            return reject_get_logs(state, &request, -32614, &message, None);
+        }
+        return forward(state, body).await;
+    }
+
+    let (status, response) = forward(state, body).await;
+    let blocks: Vec<u64> = match serde_json::from_slice::<serde_json::Value>(&response) {
+        Ok(parsed) => match parsed["result"].as_array() {
+            Some(logs) => logs.iter().filter_map(|log| block_tag(&log["blockNumber"])).collect(),
+            None => return (status, response),
+        },
+        Err(_) => return (status, response),
+    };
+    match limit {
+        LogsLimit::Results(max) if blocks.len() > max => {
+            let message = format!("query exceeds the maximum of {} results, narrow the block range", max);
+            reject_get_logs(state, &request, -32005, &message, None)
+        }
+        LogsLimit::ResultsWithSuggestedRange(max) if blocks.len() > max => {
+            let from = block_tag(&filter["fromBlock"]).unwrap_or(blocks[0]);
+            // Everything before the block of the first log that does not fit
+            let to = blocks[max].saturating_sub(1).max(from);
+            let message = format!(
+                "query returned more than {} results. Try with this block range [{:#x}, {:#x}].",
+                max, from, to
+            );
+            let data = serde_json::json!({ "from": format!("{:#x}", from), "to": format!("{:#x}", to) });
+            reject_get_logs(state, &request, -32005, &message, Some(data))
+        }
+        _ => (status, response),
+    }
+}
+
+fn reject_get_logs(
+    state: &ProxyState,
+    request: &serde_json::Value,
+    code: i64,
+    message: &str,
+    data: Option<serde_json::Value>,
+) -> (u16, Vec<u8>) {
+    state.stats.lock().unwrap().limited += 1;
+    state.log.push_stdout(&format!("eth_getLogs {} -> {}", request["params"], message));
+    let mut error = serde_json::json!({ "code": code, "message": message });
+    if let Some(data) = data {
+        error["data"] = data;
+    }
+    let body = serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "error": error });
+    (200, body.to_string().into_bytes())
+}
+
+/// Head block of the upstream, for filters ending at `latest`
+async fn upstream_head(state: &ProxyState) -> Option<u64> {
+    let request = serde_json::json!({ "jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 1 });
+    let (_, response) = forward(state, request.to_string().into_bytes()).await;
+    let response: serde_json::Value = serde_json::from_slice(&response).ok()?;
+    block_tag(&response["result"])
+}
+
+/// Send `body` upstream; an unreachable upstream is answered with 502
+async fn forward(state: &ProxyState, body: Vec<u8>) -> (u16, Vec<u8>) {
+    let upstream = match &state.upstream {
+        Upstream::Http(upstream) => upstream,
+        Upstream::Replay(replay) => return (200, replay_body(state, replay, &body)),
+    };
+    let response = state.client.post(upstream)
+        .header("Content-Type", "application/json")
+        .body(body.clone())
+        .send()
+        .await;
+    match response {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            match response.bytes().await {
+                Ok(bytes) => {
+                    // Only answers the upstream actually gave belong on the cassette
+                    if let (Some(recording), 200) = (&state.recording, status) {
+                        recording.cassette.lock().unwrap().record(&body, &bytes);
+                    }
+                    (status, bytes.to_vec())
+                }
+                Err(e) => (502, e.to_string().into_bytes()),
+            }
+        }
+        Err(e) => (502, e.to_string().into_bytes()),
+    }
+}
+
+/// Answer a request or batch `body` from the cassette
+fn replay_body(state: &ProxyState, replay: &Mutex<Replay>, body: &[u8]) -> Vec<u8> {
+    let answer = |request: &serde_json::Value| {
+        replay.lock().unwrap().answer(request).unwrap_or_else(|| {
+            state.stats.lock().unwrap().unrecorded += 1;
+            state.log.push_stdout(&format!("not on cassette: {} {}", request["method"], request["params"]));
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": { "code": -32603, "message": "request not recorded on the cassette" }
+            })
+        })
+    };
+    let response = match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(serde_json::Value::Array(batch)) => serde_json::Value::Array(batch.iter().map(answer).collect()),
+        Ok(request) => answer(&request),
+        Err(_) => answer(&serde_json::Value::Null),
+    };
+    response.to_string().into_bytes()
+}
+
+/// Write an HTTP response announcing all of `body`, but sending only the
+/// first `truncate_at` bytes of it when set
+async fn write_response(write: &mut OwnedWriteHalf, status: u16, body: &[u8], truncate_at: Option<usize>) -> Result<()> {
+    let reason = reqwest::StatusCode::from_u16(status).ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Unknown");
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n",
+        status, reason, body.len()
+    );
+    if status == 429 {
+        response.push_str("Retry-After: 1\r\n");
+    }
+    response.push_str("\r\n");
+    write.write_all(response.as_bytes()).await?;
+    write.write_all(&body[..truncate_at.unwrap_or(body.len())]).await?;
+    write.flush().await?;
+    if truncate_at.is_some() {
+        write.shutdown().await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use anyhow::Result;
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use tracing::{error, info};
+
+use nix::sys::signal::Signal;
+
+use crate::process_group::{kill_process_group, signal_process_group, untrack_if_empty};
+use crate::process_log::ProcessLog;
+
+/// Lifecycle of a supervised child process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessState {
+    Running {
+        pid: u32,
+        started_at: SystemTime,
+    },
+    Exited {
+        /// `None` if the exit status could not be collected
+        status: Option<ExitStatus>,
+        at: SystemTime,
+        /// Whether the exit was requested through `stop`
+        expected: bool,
+    },
+}
+
+impl ProcessState {
+    pub fn is_running(&self) -> bool {
+        matches!(self, ProcessState::Running { .. })
+    }
+
+    /// An exit nobody asked for that did not end with status 0
+    pub fn is_crash(&self) -> bool {
+        match self {
+            ProcessState::Exited { status, expected, .. } => {
+                !expected && !status.map(|s| s.success()).unwrap_or(false)
+            }
+            ProcessState::Running { .. } => false,
+        }
+    }
+}
+
+/// How a `shutdown` ended
+#[derive(Debug, Clone)]
+pub struct ShutdownOutcome {
+    pub state: ProcessState,
+    /// The process ignored the signal for the whole grace period and was SIGKILLed
+    pub escalated: bool,
+}
+
+impl ShutdownOutcome {
+    /// Exited by itself within the grace period with status 0
+    pub fn is_clean(&self) -> bool {
+        !self.escalated && matches!(&self.state, ProcessState::Exited { status: Some(s), .. } if s.success())
+    }
+}
+
+/// Receives a message when any process a test depends on crashes.
+///
+/// Shared by everything a test starts; the test runner waits on it alongside
+/// the test itself and fails the test as soon as a crash is reported.
+#[derive(Debug, Clone)]
+pub struct CrashMonitor {
+    crash: Arc<watch::Sender<Option<String>>>,
+}
+
+impl Default for CrashMonitor {
+    fn default() -> Self {
+        Self { crash: Arc::new(watch::channel(None).0) }
+    }
+}
+
+impl CrashMonitor {
+    /// Record a crash; only the first one is kept
+    pub fn report(&self, message: String) {
+        self.crash.send_if_modified(|current| {
+            if current.is_none() {
+                *current = Some(message);
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Resolve with the crash message once one is reported; never resolves otherwise
+    pub async fn crashed(&self) -> String {
+        let mut rx = self.crash.subscribe();
+        loop {
+            if let Some(message) = rx.borrow_and_update().clone() {
+                return message;
+            }
+            if rx.changed().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+struct Shared {
+    state: watch::Sender<ProcessState>,
+    expected: AtomicBool,
+    crash_monitor: Mutex<Option<CrashMonitor>>,
+    log: ProcessLog,
+}
+
+impl Shared {
+    fn report_crash(&self, state: &ProcessState) {
+        if !state.is_crash() {
+            return;
+        }
+        if let Some(monitor) = self.crash_monitor.lock().unwrap().as_ref() {
+            let status = match state {
+                ProcessState::Exited { status: Some(status), .. } => status.to_string(),
+                _ => "unknown status".to_string(),
+            };
+            let tail: Vec<String> = self.log.tail(10).iter().map(|l| format!("  {}", l)).collect();
+            monitor.report(format!(
+                "{} crashed unexpectedly ({}){}",
+                self.log.name(),
+                status,
+                if tail.is_empty() { String::new() } else { format!("\nLast output:\n{}", tail.join("\n")) },
+            ));
+        }
+    }
+}
+
+/// Kills the process group when the last `SupervisedChild` clone goes away
+struct Handle {
+    pid: u32,
+    shared: Arc<Shared>,
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        if self.shared.state.borrow().is_running() {
+            self.shared.expected.store(true, Ordering::SeqCst);
+            let _ = kill_process_group(self.pid);
+        }
+    }
+}
+
+/// A child process owned by a background task that records when and how it exits.
+///
+/// Clones share the same process; it is killed when the last clone is dropped.
+#[derive(Clone)]
+pub struct SupervisedChild {
+    handle: Arc<Handle>,
+}
+
+impl std::fmt::Debug for SupervisedChild {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SupervisedChild")
+            .field("name", &self.handle.shared.log.name())
+            .field("state", &*self.handle.shared.state.borrow())
+            .finish()
+    }
+}
+
+impl SupervisedChild {
+    /// Take ownership of `child` (started with `process_group::spawn_tracked`);
+    /// `log` names the process in crash reports and supplies its recent output
+    pub fn spawn(mut child: tokio::process::Child, log: ProcessLog) -> Result<Self> {
+        let pid = child.id()
+            .ok_or_else(|| anyhow::anyhow!("{} exited before it could be supervised", log.name()))?;
+        let shared = Arc::new(Shared {
+            state: watch::channel(ProcessState::Running { pid, started_at: SystemTime::now() }).0,
+            expected: AtomicBool::new(false),
+            crash_monitor: Mutex::new(None),
+            log,
+        });
+
+        let task_shared = shared.clone();
+        tokio::spawn(async move {
+            let status = child.wait().await.ok();
+            untrack_if_empty(pid);
+            let state = ProcessState::Exited {
+                status,
+                at: SystemTime::now(),
+                expected: task_shared.expected.load(Ordering::SeqCst),
+            };
+            if state.is_crash() {
+                error!("{} (pid {}) exited unexpectedly: {:?}", task_shared.log.name(), pid, status);
+            } else {
+                info!("{} (pid {}) exited: {:?}", task_shared.log.name(), pid, status);
+            }
+            // Publish before reporting so `report_crashes_to` cannot miss it
+            task_shared.state.send_replace(state.clone());
+            task_shared.report_crash(&state);
+        });
+
+        Ok(Self { handle: Arc::new(Handle { pid, shared }) })
+    }
+
+    pub fn state(&self) -> ProcessState {
+        self.handle.shared.state.borrow().clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handle.shared.state.borrow().is_running()
+    }
+
+    /// Watch state changes (running -> exited)
+    pub fn subscribe(&self) -> watch::Receiver<ProcessState> {
+        self.handle.shared.state.subscribe()
+    }
+
+    /// Report unexpected crashes from now on to `monitor`. A crash that
+    /// already happened is reported immediately.
+    pub fn report_crashes_to(&self, monitor: CrashMonitor) {
+        let shared = &self.handle.shared;
+        *shared.crash_monitor.lock().unwrap() = Some(monitor);
+        let state = shared.state.borrow().clone();
+        shared.report_crash(&state);
+    }
+
+    /// Wait until the process has exited, or fail after `timeout`
+    pub async fn wait_for_exit(&self, timeout: Duration) -> Result<ProcessState> {
+        let mut rx = self.subscribe();
+        let exited = tokio::time::timeout(timeout, rx.wait_for(|s| !s.is_running())).await;
+        match exited {
+            Ok(Ok(state)) => Ok(state.clone()),
+            Ok(Err(_)) => Err(anyhow::anyhow!("{} supervisor went away", self.handle.shared.log.name())),
+            Err(_) => Err(anyhow::anyhow!(
+                "{} (pid {}) did not exit within {:?}",
+                self.handle.shared.log.name(),
+                self.handle.pid,
+                timeout
+            )),
+        }
+    }
+
+    /// Send `signal` to the process group and give it `grace_period` to exit,
+    /// escalating to SIGKILL if it does not. The exit is marked as expected.
+    pub async fn shutdown(&self, signal: Signal, grace_period: Duration) -> Result<ShutdownOutcome> {
+        self.handle.shared.expected.store(true, Ordering::SeqCst);
+        if !self.is_running() {
+            return Ok(ShutdownOutcome { state: self.state(), escalated: false });
+        }
+
+        info!("Sending {} to {} (pid {})", signal, self.handle.shared.log.name(), self.handle.pid);
+        signal_process_group(self.handle.pid, signal)?;
+        if let Ok(state) = self.wait_for_exit(grace_period).await {
+            return Ok(ShutdownOutcome { state, escalated: false });
+        }
+
+        error!(
+            "{} did not exit within {:?} of {}, sending SIGKILL",
+            self.handle.shared.log.name(), grace_period, signal
+        );
+        let state = self.stop(Duration::from_secs(5)).await?;
+        Ok(ShutdownOutcome { state, escalated: true })
+    }
+
+    /// Kill the whole process group and wait for the exit to be recorded.
+    /// The exit is marked as expected so it is not reported as a crash.
+    pub async fn stop(&self, timeout: Duration) -> Result<ProcessState> {
+        self.handle.shared.expected.store(true, Ordering::SeqCst);
+        if self.is_running() {
+            kill_process_group(self.handle.pid)?;
+        }
+        self.wait_for_exit(timeout).await
+    }
+}
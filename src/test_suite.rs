@@ -1,9 +1,13 @@
 use anyhow::{Result, Context};
 use tracing::{info, warn};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use tempfile::TempDir;
+use alloy::dyn_abi::DynSolValue;
 
-use crate::anvil_setup::AnvilInstance;
+use crate::anvil_setup::{erc20_transfer_request, AnvilInstance, AnvilOptions, ChainLog};
+use crate::contracts::Deployment;
+use crate::live_feeder::LiveFeeder;
 use crate::rindexer_client::RindexerInstance;
 // Config structs for Rindexer
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -22,9 +26,49 @@ pub struct RindexerConfig {
 pub struct NetworkConfig {
     pub name: String,
     pub chain_id: u64,
+    /// JSON-RPC endpoint, `http(s)://` or `ws(s)://`; see `set_rpc`
     pub rpc: String,
 }
 
+/// How rindexer reaches a network's JSON-RPC endpoint, told apart by URL scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcTransport {
+    Http,
+    WebSocket,
+}
+
+impl RpcTransport {
+    /// The transport of a `http(s)://` or `ws(s)://` endpoint
+    pub fn of(url: &str) -> Result<Self> {
+        match url.split_once("://").map(|(scheme, _)| scheme) {
+            Some("http" | "https") => Ok(RpcTransport::Http),
+            Some("ws" | "wss") => Ok(RpcTransport::WebSocket),
+            _ => Err(anyhow::anyhow!("RPC endpoint {} is neither http(s):// nor ws(s)://", url)),
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// The transport rindexer will use for this network
+    pub fn transport(&self) -> Result<RpcTransport> {
+        RpcTransport::of(&self.rpc)
+            .with_context(|| format!("Network {}", self.name))
+    }
+
+    /// Point the network at `rpc`, which has to use `transport`, so a test that
+    /// means to exercise WebSocket cannot silently end up on HTTP or vice versa
+    pub fn set_rpc(&mut self, rpc: &str, transport: RpcTransport) -> Result<()> {
+        let actual = RpcTransport::of(rpc)?;
+        if actual != transport {
+            return Err(anyhow::anyhow!(
+                "Network {} needs a {:?} endpoint, got {:?} endpoint {}", self.name, transport, actual, rpc
+            ));
+        }
+        self.rpc = rpc.to_string();
+        Ok(())
+    }
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct StorageConfig {
     pub postgres: PostgresConfig,
@@ -67,10 +111,19 @@ pub struct EventConfig {
     pub name: String,
 }
 use crate::health_client::HealthClient;
+use crate::ports::TestPorts;
+use crate::process_log::ProcessLogs;
+use crate::supervisor::CrashMonitor;
+use crate::rindexer_cli::RindexerCli;
+use crate::rpc_proxy::RpcProxy;
+use crate::cassette::CassetteMode;
+use crate::ws_proxy::WsProxy;
 
 /// Shared context for all tests - provides common infrastructure
 pub struct TestContext {
     pub anvil: AnvilInstance,
+    /// Extra local chains started with `start_anvil`, by network name
+    pub anvils: BTreeMap<String, AnvilInstance>,
     pub rindexer: Option<RindexerInstance>,
     pub graphql: Option<RindexerInstance>,
     pub test_contract_address: Option<String>,
@@ -78,26 +131,36 @@ pub struct TestContext {
     pub project_path: PathBuf,
     pub rindexer_binary: String,
     pub health_client: Option<HealthClient>,
+    pub ports: TestPorts,
+    pub live_feeder: Option<LiveFeeder>,
+    /// Postgres containers started for this test; always removed on teardown
+    pub postgres_containers: Vec<String>,
+    /// Output of every process started for this test
+    pub logs: ProcessLogs,
+    /// Keep the temporary project directory instead of deleting it on teardown
+    pub keep_temp: bool,
+    /// Processes started through this context report unexpected crashes here
+    pub crash_monitor: CrashMonitor,
+    /// Fault-injection proxies started with `start_rpc_proxy`; stopped on teardown
+    pub rpc_proxies: Vec<RpcProxy>,
+    /// WebSocket proxies started with `start_ws_proxy`; stopped on teardown
+    pub ws_proxies: Vec<WsProxy>,
 }
 
 // TestSuite is now a separate struct for test results
 
 impl TestContext {
-    pub async fn new(rindexer_binary: String, anvil_port: u16, health_port: u16) -> Result<Self> {
-        info!("Setting up fresh test context...");
-        
-        // Kill any existing Anvil processes and start fresh
-        info!("Killing any existing Anvil processes...");
-        let _ = std::process::Command::new("pkill")
-            .arg("-f")
-            .arg("anvil")
-            .output();
+    /// `logs` receives the output of every process started for this test.
+    /// Anvil is started with `anvil_options` on the test's Anvil port.
+    pub async fn new(rindexer_binary: String, ports: TestPorts, logs: ProcessLogs, anvil_options: AnvilOptions) -> Result<Self> {
+        info!("Setting up fresh test context (anvil={}, health={}, graphql={})...", ports.anvil, ports.health, ports.graphql);
         
-        // Wait for processes to be killed and port to be free
-        wait_for_port_free(anvil_port, 10).await?;
+        // Anvil processes we did not start are never killed; wait for the port
+        // to be released by the previous test instead
+        wait_for_port_free(ports.anvil, 10).await?;
         
         // Start a fresh Anvil instance
-        let anvil = AnvilInstance::start_local("0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80").await
+        let anvil = AnvilInstance::start(anvil_options.with_port(ports.anvil), logs.create("anvil")).await
             .context("Failed to start Anvil instance")?;
         
         info!("Anvil ready at: {}", anvil.rpc_url);
@@ -112,59 +175,255 @@ impl TestContext {
         
         Ok(Self {
             anvil,
+            anvils: BTreeMap::new(),
             rindexer: None,
             graphql: None,
             test_contract_address: None,
             temp_dir: Some(temp_dir),
             project_path,
             rindexer_binary,
-            health_client: Some(HealthClient::new(health_port)),
+            health_client: Some(HealthClient::new(ports.health)),
+            ports,
+            live_feeder: None,
+            postgres_containers: Vec::new(),
+            logs,
+            keep_temp: false,
+            crash_monitor: CrashMonitor::default(),
+            rpc_proxies: Vec::new(),
+            ws_proxies: Vec::new(),
         })
     }
+
+    /// Create a Rindexer instance for this test's project, wired to the test's health and GraphQL ports
+    pub fn new_rindexer_instance(&self) -> RindexerInstance {
+        RindexerInstance::new(&self.rindexer_binary, self.project_path.clone())
+            .with_log(self.logs.create("rindexer"))
+            .with_crash_monitor(self.crash_monitor.clone())
+            .with_env("HEALTH_PORT", &self.ports.health.to_string())
+            .with_env("GRAPHQL_PORT", &self.ports.graphql.to_string())
+    }
+
+    /// Typed rindexer CLI whose command output lands in this test's logs
+    pub fn cli(&self) -> RindexerCli {
+        RindexerCli::new(&self.rindexer_binary)
+            .with_logs(self.logs.clone())
+            .with_crash_monitor(self.crash_monitor.clone())
+    }
+
+    /// Default GraphQL endpoint for this test's GraphQL port
+    pub fn graphql_url(&self) -> String {
+        format!("http://localhost:{}/graphql", self.ports.graphql)
+    }
     
-    pub async fn cleanup(&mut self) -> Result<()> {
+    /// Tear down everything this context started. Every step runs even if an
+    /// earlier one fails; the errors are returned so they can be reported.
+    pub async fn cleanup(&mut self) -> Vec<String> {
         info!("Cleaning up test suite...");
+        let mut errors = Vec::new();
+        
+        // Stop the live feeder first so it stops sending transactions
+        if let Some(feeder) = self.live_feeder.take() {
+            feeder.stop();
+        }
         
         // Stop Rindexer if running
         if let Some(mut rindexer) = self.rindexer.take() {
             if let Err(e) = rindexer.stop().await {
                 warn!("Error stopping Rindexer: {}", e);
+                errors.push(format!("Error stopping Rindexer: {}", e));
             }
         }
         // Stop GraphQL if running
         if let Some(mut graphql) = self.graphql.take() {
             if let Err(e) = graphql.stop().await {
                 warn!("Error stopping GraphQL: {}", e);
+                errors.push(format!("Error stopping GraphQL: {}", e));
+            }
+        }
+        
+        // Remove Postgres containers
+        // Names are kept so the runner can check the removal actually happened
+        for container in &self.postgres_containers {
+            if let Err(e) = crate::docker::stop_postgres_container(container).await {
+                warn!("Error removing Postgres container {}: {}", container, e);
+                errors.push(format!("Error removing Postgres container {}: {}", container, e));
             }
         }
         
-        // Anvil will be cleaned up automatically when the process is dropped
+        for proxy in self.rpc_proxies.drain(..) {
+            proxy.stop();
+        }
+        for proxy in self.ws_proxies.drain(..) {
+            proxy.stop();
+        }
+        
+        if let Err(e) = self.anvil.stop().await {
+            warn!("Error stopping Anvil: {}", e);
+            errors.push(format!("Error stopping Anvil: {}", e));
+        }
+        for (network, anvil) in self.anvils.iter_mut() {
+            if let Err(e) = anvil.stop().await {
+                warn!("Error stopping Anvil for {}: {}", network, e);
+                errors.push(format!("Error stopping Anvil for {}: {}", network, e));
+            }
+        }
         
         // TempDir will be cleaned up automatically on drop
-        self.temp_dir.take();
+        if let Some(temp_dir) = self.temp_dir.take() {
+            if self.keep_temp {
+                let kept = temp_dir.keep();
+                info!("Keeping test project directory at {}", kept.display());
+            }
+        }
         
         info!("Test suite cleanup completed");
+        errors
+    }
+    
+    /// Start an ephemeral Postgres container for this test and wait until it accepts connections.
+    /// The container is removed on teardown regardless of the test outcome.
+    pub async fn start_postgres(&mut self) -> Result<u16> {
+        let (container_name, pg_port) = crate::docker::start_postgres_container().await
+            .context("Failed to start postgres container")?;
+        self.postgres_containers.push(container_name);
+        
+        for _ in 0..40 {
+            if tokio_postgres::connect(&postgres_connection_string(pg_port), tokio_postgres::NoTls).await.is_ok() {
+                return Ok(pg_port);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        }
+        Err(anyhow::anyhow!("Postgres did not become ready in time"))
+    }
+    
+    /// Start another local chain named `network` for multi-chain tests. The port
+    /// is picked automatically unless `options` sets one; the chain id must differ
+    /// from every chain already running in this context.
+    pub async fn start_anvil(&mut self, network: &str, options: AnvilOptions) -> Result<&AnvilInstance> {
+        self.check_new_chain(network, options.chain_id)?;
+        
+        let anvil = AnvilInstance::start(options, self.logs.create(&format!("anvil-{}", network))).await
+            .with_context(|| format!("Failed to start Anvil for {}", network))?;
+        info!("Anvil for {} ready at: {} (chain id {})", network, anvil.rpc_url, anvil.chain_id);
+        Ok(self.anvils.entry(network.to_string()).or_insert(anvil))
+    }
+    
+    /// Start a fork of the test's own Anvil at `block` as an extra chain named
+    /// `network`. The same rules as `start_anvil` apply to `options`.
+    pub async fn start_fork(&mut self, network: &str, block: u64, options: AnvilOptions) -> Result<&AnvilInstance> {
+        self.check_new_chain(network, options.chain_id)?;
+        
+        let anvil = AnvilInstance::start_fork(&self.anvil, block, options, self.logs.create(&format!("anvil-{}", network))).await
+            .with_context(|| format!("Failed to fork Anvil for {}", network))?;
+        info!("Fork {} of block {} ready at: {} (chain id {})", network, block, anvil.rpc_url, anvil.chain_id);
+        Ok(self.anvils.entry(network.to_string()).or_insert(anvil))
+    }
+    
+    fn check_new_chain(&self, network: &str, chain_id: u64) -> Result<()> {
+        if self.anvils.contains_key(network) {
+            return Err(anyhow::anyhow!("A chain named {} is already running", network));
+        }
+        if std::iter::once(&self.anvil).chain(self.anvils.values()).any(|a| a.chain_id == chain_id) {
+            return Err(anyhow::anyhow!("Chain id {} is already used by another local chain", chain_id));
+        }
         Ok(())
     }
     
+    /// Put a fault-injection proxy in front of the chain `network`: `anvil` is
+    /// the test's own chain, anything else one started with `start_anvil`.
+    /// Point rindexer at the returned proxy's `url` to route its calls through it.
+    pub async fn start_rpc_proxy(&mut self, network: &str) -> Result<RpcProxy> {
+        let upstream = if network == "anvil" { &self.anvil } else { self.anvil_for(network)? };
+        let proxy = RpcProxy::start(&upstream.rpc_url, self.logs.create(&format!("rpc-proxy-{}", network))).await?;
+        self.rpc_proxies.push(proxy.clone());
+        Ok(proxy)
+    }
+    
+    /// Put a `WsProxy` in front of the WebSocket endpoint of the chain `network`,
+    /// named like for `start_rpc_proxy`
+    pub async fn start_ws_proxy(&mut self, network: &str) -> Result<WsProxy> {
+        let upstream = if network == "anvil" { &self.anvil } else { self.anvil_for(network)? };
+        let proxy = WsProxy::start(&upstream.ws_url, self.logs.create(&format!("ws-proxy-{}", network))).await?;
+        self.ws_proxies.push(proxy.clone());
+        Ok(proxy)
+    }
+    
+    /// A proxy for the live network behind `upstream_env`, replaying cassette
+    /// `name` instead when it exists (see `CassetteMode::select`). A recording
+    /// proxy only writes the cassette once the test calls `save_cassette`.
+    pub async fn start_cassette_proxy(&mut self, name: &str, upstream_env: &str) -> Result<RpcProxy> {
+        let log = self.logs.create(&format!("rpc-cassette-{}", name));
+        let proxy = match CassetteMode::select(name, upstream_env)? {
+            CassetteMode::Replay(path) => RpcProxy::replay(&path, log).await?,
+            CassetteMode::Record { upstream, path } => RpcProxy::record(&upstream, &path, log).await?,
+            CassetteMode::Live(upstream) => RpcProxy::start(&upstream, log).await?,
+        };
+        self.rpc_proxies.push(proxy.clone());
+        Ok(proxy)
+    }
+    
+    /// An extra chain started with `start_anvil`
+    pub fn anvil_for(&self, network: &str) -> Result<&AnvilInstance> {
+        self.anvils.get(network)
+            .ok_or_else(|| anyhow::anyhow!("No local chain named {}", network))
+    }
+    
+    /// Deploy the test contract on an extra chain started with `start_anvil`
+    pub async fn deploy_test_contract_on(&self, network: &str) -> Result<String> {
+        let log = self.logs.create(&format!("deploy-{}", network));
+        self.anvil_for(network)?.deploy_test_contract(log).await
+    }
+    
+    /// Deploy `name` on the test's Anvil from its artifact (built with forge when not checked in)
+    pub async fn deploy_contract(&self, name: &str, constructor_args: &[DynSolValue]) -> Result<Deployment> {
+        self.anvil.deploy_contract(name, constructor_args, self.logs.create("deploy")).await
+    }
+    
     /// Deploy a test contract using the Anvil instance
     pub async fn deploy_test_contract(&mut self) -> Result<String> {
-        let address = self.anvil.deploy_test_contract().await?;
+        let address = self.anvil.deploy_test_contract(self.logs.create("deploy")).await?;
         self.test_contract_address = Some(address.clone());
         Ok(address)
     }
     
     /// Create a minimal Rindexer configuration
     pub fn create_minimal_config(&self) -> RindexerConfig {
-        crate::rindexer_client::RindexerInstance::create_minimal_config(&self.anvil.rpc_url)
+        let mut config = crate::rindexer_client::RindexerInstance::create_minimal_config(&self.anvil.rpc_url);
+        config.networks[0].chain_id = self.anvil.chain_id;
+        config
     }
     
     /// Create a configuration with a specific contract
     pub fn create_contract_config(&self, contract_address: &str) -> RindexerConfig {
-        crate::rindexer_client::RindexerInstance::create_contract_config(&self.anvil.rpc_url, contract_address)
+        let mut config = crate::rindexer_client::RindexerInstance::create_contract_config(&self.anvil.rpc_url, contract_address);
+        config.networks[0].chain_id = self.anvil.chain_id;
+        config
     }
     
     pub async fn start_rindexer(&mut self, config: RindexerConfig) -> Result<()> {
+        self.start_rindexer_with_env(config, HashMap::new()).await
+    }
+
+    /// Write the project for `config` and start the indexer with `env` added to
+    /// its environment, e.g. `postgres_env(pg_port)`
+    pub async fn start_rindexer_with_env(&mut self, config: RindexerConfig, env: HashMap<String, String>) -> Result<()> {
+        self.write_project(&config)?;
+
+        // Create Rindexer instance and start indexer
+        let mut rindexer = self.new_rindexer_instance();
+        rindexer.env.extend(env);
+        
+        rindexer.start_indexer().await
+            .context("Failed to start Rindexer indexer")?;
+        
+        self.rindexer = Some(rindexer);
+        info!("Rindexer started successfully");
+        
+        Ok(())
+    }
+
+    /// Copy the repo's ABIs into the project and write `config` as its `rindexer.yaml`
+    pub fn write_project(&self, config: &RindexerConfig) -> Result<()> {
         // Create abis directory and copy all ABI files from repo abis/
         let abis_dir = self.project_path.join("abis");
         std::fs::create_dir_all(&abis_dir)
@@ -184,25 +443,19 @@ impl TestContext {
             }
         }
         
+        for network in &config.networks {
+            network.transport()?;
+        }
+
         // Write the Rindexer configuration
         let config_path = self.project_path.join("rindexer.yaml");
-        let config_yaml = serde_yaml::to_string(&config)
+        let config_yaml = serde_yaml::to_string(config)
             .context("Failed to serialize config to YAML")?;
         
         std::fs::write(&config_path, config_yaml)
             .context("Failed to write config file")?;
         
         info!("Created Rindexer project at: {:?}", self.project_path);
-        
-        // Create Rindexer instance and start indexer
-        let mut rindexer = RindexerInstance::new(&self.rindexer_binary, self.project_path.clone());
-        
-        rindexer.start_indexer().await
-            .context("Failed to start Rindexer indexer")?;
-        
-        self.rindexer = Some(rindexer);
-        info!("Rindexer started successfully");
-        
         Ok(())
     }
     
@@ -269,6 +522,93 @@ impl TestContext {
         let lines: Vec<&str> = content.lines().collect();
         Ok(if lines.len() > 1 { lines.len() - 1 } else { 0 }) // Subtract header
     }
+
+    /// Every Transfer in the CSV output, identified like logs on the chain
+    pub fn read_indexed_transfers(&self) -> Result<Vec<ChainLog>> {
+        let path = self.get_csv_output_path().join("SimpleERC20").join("simpleerc20-transfer.csv");
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let mut lines = content.lines();
+        let headers: Vec<&str> = lines.next().unwrap_or("").split(',').collect();
+        let column = |name: &str| headers.iter().position(|h| *h == name)
+            .ok_or_else(|| anyhow::anyhow!("{} column not found in {}", name, path.display()));
+        let (block_number, block_hash, tx_hash, log_index) =
+            (column("block_number")?, column("block_hash")?, column("tx_hash")?, column("log_index")?);
+
+        lines.filter(|line| !line.trim().is_empty()).map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            let field = |i: usize| fields.get(i).map(|f| f.trim())
+                .ok_or_else(|| anyhow::anyhow!("Short CSV row: {}", line));
+            Ok(ChainLog {
+                block_number: field(block_number)?.parse()?,
+                block_hash: field(block_hash)?.to_lowercase(),
+                tx_hash: field(tx_hash)?.to_lowercase(),
+                log_index: field(log_index)?.parse()?,
+            })
+        }).collect()
+    }
+
+    /// Send `count` SimpleERC20 transfers from the deployer, one per block under automine
+    pub async fn send_transfers(&self, contract_address: &str, count: u64) -> Result<()> {
+        let deployer = self.anvil.deployer().address.to_string();
+        for i in 0..count {
+            self.anvil.send_unsigned(erc20_transfer_request(
+                &deployer,
+                contract_address,
+                &format!("0x7e{:038x}", i),
+                100 + i,
+            )).await?;
+        }
+        Ok(())
+    }
+
+    /// Stop rindexer gracefully so everything it indexed is flushed before reading it
+    pub async fn stop_rindexer(&mut self) -> Result<()> {
+        if let Some(mut rindexer) = self.rindexer.take() {
+            rindexer.stop().await?;
+        }
+        Ok(())
+    }
+
+    /// Stop rindexer and check the Transfers in its CSV output against `canonical`
+    pub async fn stop_and_compare(&mut self, canonical: &[ChainLog]) -> Result<Vec<ChainLog>> {
+        self.stop_rindexer().await?;
+        let indexed = self.read_indexed_transfers()?;
+        compare_with_canonical(&indexed, canonical)?;
+        Ok(indexed)
+    }
+}
+
+/// Every indexed log must be on the canonical chain exactly once, and every
+/// canonical log must be indexed
+pub fn compare_with_canonical(indexed: &[ChainLog], canonical: &[ChainLog]) -> Result<()> {
+    let canonical_set: HashSet<&ChainLog> = canonical.iter().collect();
+
+    let mut seen: HashMap<(&str, u64), usize> = HashMap::new();
+    for log in indexed {
+        *seen.entry((log.tx_hash.as_str(), log.log_index)).or_default() += 1;
+    }
+
+    let orphaned: Vec<&ChainLog> = indexed.iter().filter(|log| !canonical_set.contains(log)).collect();
+    let duplicates: Vec<_> = seen.iter().filter(|(_, count)| **count > 1).collect();
+    let missing: Vec<&ChainLog> = canonical.iter()
+        .filter(|log| !seen.contains_key(&(log.tx_hash.as_str(), log.log_index)))
+        .collect();
+
+    if orphaned.is_empty() && duplicates.is_empty() && missing.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "Indexed output does not match the canonical chain: {} orphaned, {} duplicated, {} missing\n\
+         orphaned: {:?}\nduplicated: {:?}\nmissing: {:?}",
+        orphaned.len(), duplicates.len(), missing.len(),
+        orphaned, duplicates, missing
+    ))
+}
+
+/// libpq-style connection string for a test Postgres container
+pub fn postgres_connection_string(pg_port: u16) -> String {
+    format!("host=localhost port={} user=postgres password=postgres dbname=postgres", pg_port)
 }
 
 async fn wait_for_port_free(port: u16, max_attempts: u32) -> Result<()> {
@@ -287,5 +627,8 @@ async fn wait_for_port_free(port: u16, max_attempts: u32) -> Result<()> {
             }
         }
     }
-    Err(anyhow::anyhow!("Port {} is still in use after {} attempts", port, max_attempts))
+    Err(anyhow::anyhow!(
+        "Port {} is still in use after {} attempts; processes the harness did not start are left alone, so stop whatever is listening there or run with --jobs > 1",
+        port, max_attempts
+    ))
 }
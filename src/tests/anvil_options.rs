@@ -0,0 +1,161 @@
+use anyhow::Result;
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::anvil_setup::{AnvilOptions, Mining, TRANSFER_TOPIC};
+use crate::test_suite::{RpcTransport, TestContext};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Anvil's default dev mnemonic; keeps the suite's deployer key funded
+const DEV_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+pub struct AnvilOptionsTests;
+
+impl TestModule for AnvilOptionsTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_custom_chain_manual_mining",
+                "Rindexer connects to a chain with a custom chain id that only mines on demand",
+                custom_chain_manual_mining_test,
+            ).with_timeout(60)
+             .with_tags(&[TestTag::Historic])
+             .with_anvil(
+                 AnvilOptions::new()
+                     .with_chain_id(1337)
+                     .with_mining(Mining::Manual)
+                     .with_mnemonic(DEV_MNEMONIC)
+                     .with_accounts(3, 500)
+                     .with_gas(60_000_000, 2_000_000_000)
+                     .with_base_fee(1_000_000_000)
+                     .with_hardfork("cancun")
+                     .with_code_size_limit(49_152),
+             ),
+            TestDefinition::new(
+                "test_auto_mining_historic",
+                "Rindexer indexes a contract on a chain that mines one block per transaction",
+                auto_mining_historic_test,
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_chain_id(31338).with_mining(Mining::Auto)),
+            TestDefinition::new(
+                "test_state_file_restart",
+                "A chain restarted from its state file keeps its contract and logs, and rindexer indexes them",
+                state_file_restart_test,
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"]),
+        ]
+    }
+}
+
+fn custom_chain_manual_mining_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Custom Chain Manual Mining Test");
+
+        let chain_id = context.anvil.get_chain_id().await?;
+        if chain_id != 1337 {
+            return Err(anyhow::anyhow!("Anvil reports chain id {}, expected 1337", chain_id));
+        }
+
+        // Without mining nothing should move on its own
+        let before = context.anvil.get_block_number().await?;
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        let idle = context.anvil.get_block_number().await?;
+        if idle != before {
+            return Err(anyhow::anyhow!("Chain advanced from {} to {} with mining disabled", before, idle));
+        }
+        context.anvil.mine_block().await?;
+        let mined = context.anvil.get_block_number().await?;
+        if mined != before + 1 {
+            return Err(anyhow::anyhow!("evm_mine moved the chain from {} to {}, expected one block", before, mined));
+        }
+
+        // The generated config carries the custom chain id
+        let config = context.create_minimal_config();
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(10).await?;
+        if !context.is_rindexer_running() {
+            return Err(anyhow::anyhow!("Rindexer is not running against chain 1337"));
+        }
+
+        info!("✓ Custom Chain Manual Mining Test PASSED: chain id {}, block {}", chain_id, mined);
+        Ok(())
+    })
+}
+
+fn auto_mining_historic_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Auto Mining Historic Test");
+
+        let contract_address = context.deploy_test_contract().await?;
+
+        // Auto mining produces exactly one block for the deployment
+        let block = context.anvil.get_block_number().await?;
+        if block != 1 {
+            return Err(anyhow::anyhow!("Expected the deployment in block 1 with auto mining, chain is at {}", block));
+        }
+
+        let config = context.create_contract_config(&contract_address);
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(30).await?;
+
+        let indexed = context.get_event_count()?;
+        if indexed == 0 {
+            return Err(anyhow::anyhow!("No Transfer events indexed on chain 31338"));
+        }
+
+        info!("✓ Auto Mining Historic Test PASSED: {} events indexed", indexed);
+        Ok(())
+    })
+}
+
+fn state_file_restart_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Anvil State File Restart Test");
+
+        // Next to the project rather than in it, so rindexer never sees it
+        let state = context.project_path.with_file_name("anvil-state.json");
+        let options = |chain_id| AnvilOptions::new()
+            .with_chain_id(chain_id)
+            .with_mining(Mining::Auto)
+            .with_state(&state);
+
+        context.start_anvil("before", options(31340)).await?;
+        let contract_address = context.deploy_test_contract_on("before").await?;
+        let before = context.anvil_for("before")?;
+        let head = before.get_block_number().await?;
+        let transfers = before.count_logs(&contract_address, TRANSFER_TOPIC).await?;
+        // Anvil only writes the state file when it exits on its own
+        if let Some(before) = context.anvils.get_mut("before") {
+            before.stop_gracefully(Duration::from_secs(10)).await?;
+        }
+        if !state.exists() {
+            return Err(anyhow::anyhow!("Anvil exited without writing {}", state.display()));
+        }
+
+        let after = context.start_anvil("after", options(31341)).await?;
+        let (rpc_url, chain_id) = (after.rpc_url.clone(), after.chain_id);
+        let restored_head = after.get_block_number().await?;
+        let restored = after.get_logs(&contract_address, TRANSFER_TOPIC).await?;
+        if restored_head < head || restored.len() != transfers {
+            return Err(anyhow::anyhow!(
+                "Restarted chain is at block {} with {} Transfer logs, expected block {} with {}",
+                restored_head, restored.len(), head, transfers
+            ));
+        }
+
+        let mut config = context.create_contract_config(&contract_address);
+        config.networks[0].set_rpc(&rpc_url, RpcTransport::Http)?;
+        config.networks[0].chain_id = chain_id;
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(30).await?;
+        let indexed = context.stop_and_compare(&restored).await?;
+
+        info!("✓ Anvil State File Restart Test PASSED: {} events indexed from the restored chain", indexed.len());
+        Ok(())
+    })
+}
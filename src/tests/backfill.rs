@@ -0,0 +1,95 @@
+use anyhow::Result;
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+use std::time::Instant;
+
+use crate::anvil_setup::AnvilOptions;
+use crate::fixtures::FixtureManifest;
+use crate::test_suite::{RindexerConfig, TestContext};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Chain every backfill test boots from; built once and cached under target/
+const FIXTURE: &str = "backfill-20k";
+
+pub struct BackfillTests;
+
+impl TestModule for BackfillTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_backfill_fixture_full",
+                "Rindexer backfills all 20,000 Transfers of a pre-built fixture chain",
+                backfill_full_test,
+            ).with_timeout(600)
+             .with_tags(&[TestTag::Historic, TestTag::Slow])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_fixture(FIXTURE)),
+            TestDefinition::new(
+                "test_backfill_fixture_range",
+                "Rindexer backfills exactly the Transfers between a start and end block of a fixture chain",
+                backfill_range_test,
+            ).with_timeout(600)
+             .with_tags(&[TestTag::Historic, TestTag::Slow])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_fixture(FIXTURE)),
+        ]
+    }
+}
+
+fn backfill_full_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Backfill Fixture Full Test");
+
+        let manifest = fixture_manifest(context)?;
+        let config = context.create_contract_config(&manifest.contract_address);
+        backfill(context, config, manifest.transfer_events).await?;
+
+        info!("✓ Backfill Fixture Full Test PASSED: {} events", manifest.transfer_events);
+        Ok(())
+    })
+}
+
+fn backfill_range_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Backfill Fixture Range Test");
+
+        let manifest = fixture_manifest(context)?;
+        // A window in the middle of the fixture, away from the deployment and the head
+        let start = manifest.deploy_block + manifest.head_block / 4;
+        let end = manifest.deploy_block + manifest.head_block / 2;
+        let expected = manifest.transfers_between(start, end);
+
+        let mut config = context.create_contract_config(&manifest.contract_address);
+        let detail = &mut config.contracts[0].details[0];
+        detail.start_block = start.to_string();
+        detail.end_block = Some(end.to_string());
+        backfill(context, config, expected).await?;
+
+        info!("✓ Backfill Fixture Range Test PASSED: {} events in blocks {}..={}", expected, start, end);
+        Ok(())
+    })
+}
+
+fn fixture_manifest(context: &TestContext) -> Result<FixtureManifest> {
+    context.anvil.fixture.clone()
+        .ok_or_else(|| anyhow::anyhow!("Anvil was not started from fixture {}", FIXTURE))
+}
+
+/// Index `config` and require exactly `expected` Transfer rows
+async fn backfill(context: &mut TestContext, config: RindexerConfig, expected: u64) -> Result<()> {
+    let started = Instant::now();
+    context.start_rindexer(config).await?;
+    context.wait_for_sync_completion(480).await?;
+    let elapsed = started.elapsed();
+
+    let indexed = context.get_event_count()? as u64;
+    if indexed != expected {
+        return Err(anyhow::anyhow!("Indexed {} Transfer events, the fixture has {}", indexed, expected));
+    }
+    info!(
+        "Backfilled {} events in {:.1}s ({:.0} events/s)",
+        indexed, elapsed.as_secs_f64(), indexed as f64 / elapsed.as_secs_f64()
+    );
+    Ok(())
+}
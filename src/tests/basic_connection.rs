@@ -4,7 +4,7 @@ use std::pin::Pin;
 use std::future::Future;
 
 use crate::test_suite::TestContext;
-use crate::tests::registry::{TestDefinition, TestModule};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
 
 pub struct BasicConnectionTests;
 
@@ -15,12 +15,13 @@ impl TestModule for BasicConnectionTests {
                 "test_1_basic_connection",
                 "Test basic Rindexer connection to Anvil with minimal configuration",
                 basic_connection_test,
-            ).with_timeout(60),
+            ).with_timeout(60)
+             .with_tags(&[TestTag::Historic]),
         ]
     }
 }
 
-fn basic_connection_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn basic_connection_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Test 1: Basic Connection Test");
         
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::process::Command;
+use tracing::{info, warn};
+
+use crate::cassette::CassetteMode;
+use crate::contracts::{has_artifact, ARTIFACTS_DIR};
+use crate::tests::registry::TestDefinition;
+
+/// An external prerequisite a test needs before any setup work is worth doing
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// A reachable Docker daemon (for ephemeral Postgres containers)
+    Docker,
+    /// Foundry's `forge` (building contracts that have no checked-in artifact)
+    Forge,
+    /// Foundry's `anvil` (every test context starts one)
+    Anvil,
+    /// A non-empty environment variable
+    EnvVar(&'static str),
+    /// A checked-in artifact for the contract, so it deploys without forge
+    Artifact(&'static str),
+    /// A replayable RPC cassette, or the live endpoint in `upstream_env` to use instead
+    Cassette { name: &'static str, upstream_env: &'static str },
+    /// The rindexer binary under test, optionally at or above a minimum version
+    Rindexer { min_version: Option<&'static str> },
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::Docker => f.write_str("docker"),
+            Capability::Forge => f.write_str("forge"),
+            Capability::Anvil => f.write_str("anvil"),
+            Capability::EnvVar(name) => write!(f, "env {}", name),
+            Capability::Artifact(name) => write!(f, "artifact {}", name),
+            Capability::Cassette { name, upstream_env } => write!(f, "cassette {} or env {}", name, upstream_env),
+            Capability::Rindexer { min_version: None } => f.write_str("rindexer"),
+            Capability::Rindexer { min_version: Some(v) } => write!(f, "rindexer >= {}", v),
+        }
+    }
+}
+
+/// Result of probing every capability required by a run, computed once up front
+#[derive(Debug, Default)]
+pub struct CapabilityReport {
+    missing: HashMap<Capability, String>,
+}
+
+impl CapabilityReport {
+    /// Probe each distinct capability required by `tests`
+    pub async fn probe(tests: &[TestDefinition], rindexer_binary: &str) -> Self {
+        let mut report = Self::default();
+        let mut probed = Vec::new();
+
+        for capability in tests.iter().flat_map(|t| t.requires.iter()) {
+            if probed.contains(capability) {
+                continue;
+            }
+            probed.push(capability.clone());
+
+            match probe_capability(capability, rindexer_binary).await {
+                Ok(()) => info!("Capability available: {}", capability),
+                Err(reason) => {
+                    warn!("Capability missing: {} ({})", capability, reason);
+                    report.missing.insert(capability.clone(), reason);
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Uniform skip reason for a test whose requirements are not met, if any
+    pub fn skip_reason(&self, test: &TestDefinition) -> Option<String> {
+        let missing: Vec<String> = test
+            .requires
+            .iter()
+            .filter_map(|c| self.missing.get(c).map(|reason| format!("{} ({})", c, reason)))
+            .collect();
+
+        if missing.is_empty() {
+            None
+        } else {
+            Some(format!("Missing capability: {}", missing.join("; ")))
+        }
+    }
+}
+
+async fn probe_capability(capability: &Capability, rindexer_binary: &str) -> Result<(), String> {
+    match capability {
+        Capability::Docker => crate::docker::ensure_docker_daemon()
+            .await
+            .map_err(|e| e.to_string()),
+        Capability::Forge => command_version("forge").map(|_| ()),
+        Capability::Anvil => command_version("anvil").map(|_| ()),
+        Capability::EnvVar(name) => match std::env::var(name) {
+            Ok(v) if !v.trim().is_empty() => Ok(()),
+            _ => Err(format!("{} not set", name)),
+        },
+        Capability::Artifact(name) => if has_artifact(name) {
+            Ok(())
+        } else {
+            Err(format!("no {}/{}.json, run make artifacts", ARTIFACTS_DIR, name))
+        },
+        Capability::Cassette { name, upstream_env } => CassetteMode::select(name, upstream_env)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        Capability::Rindexer { min_version } => probe_rindexer(rindexer_binary, *min_version),
+    }
+}
+
+/// Run `<program> --version` and return its first output line
+fn command_version(program: &str) -> Result<String, String> {
+    let output = Command::new(program)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("{} not found: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} --version exited with {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).lines().next().unwrap_or_default().to_string())
+}
+
+fn probe_rindexer(rindexer_binary: &str, min_version: Option<&str>) -> Result<(), String> {
+    if !std::path::Path::new(rindexer_binary).exists() {
+        return Err(format!("binary not found at {}", rindexer_binary));
+    }
+    let Some(min_version) = min_version else {
+        return Ok(());
+    };
+
+    let version_line = command_version(rindexer_binary)?;
+    let found = parse_version(&version_line)
+        .ok_or_else(|| format!("could not parse version from '{}'", version_line))?;
+    let required = parse_version(min_version)
+        .ok_or_else(|| format!("invalid minimum version '{}'", min_version))?;
+
+    if found >= required {
+        Ok(())
+    } else {
+        Err(format!("found {}.{}.{}", found.0, found.1, found.2))
+    }
+}
+
+/// Extract the first `major.minor.patch` triple from a version string
+fn parse_version(text: &str) -> Option<(u64, u64, u64)> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter_map(|token| {
+            let mut parts = token.split('.').map(|p| p.parse::<u64>());
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(Ok(major)), Some(Ok(minor)), patch) => {
+                    Some((major, minor, patch.and_then(|p| p.ok()).unwrap_or(0)))
+                }
+                _ => None,
+            }
+        })
+        .next()
+}
@@ -0,0 +1,195 @@
+use anyhow::Result;
+use alloy::{
+    network::EthereumWallet,
+    primitives::{keccak256, Address, Bytes, U256},
+    providers::{Provider, ProviderBuilder},
+    rpc::types::{TransactionInput, TransactionRequest},
+};
+use tracing::info;
+use std::collections::HashSet;
+use std::pin::Pin;
+use std::future::Future;
+
+use crate::accounts::TestAccount;
+use crate::anvil_setup::{AnvilInstance, AnvilOptions, Mining};
+use crate::test_suite::TestContext;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// SimpleERC20 keeps `balanceOf` in storage slot 4
+const BALANCE_OF_SLOT: u8 = 4;
+
+pub struct CheatcodeTests;
+
+impl TestModule for CheatcodeTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_cheatcodes_many_senders",
+                "Transfers from named accounts and an impersonated address, with controlled timestamps, are all indexed",
+                cheatcodes_many_senders_test,
+            ).with_timeout(90)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"])
+             // One block per transaction keeps block numbers predictable
+             .with_anvil(AnvilOptions::new().with_mining(Mining::Auto)),
+        ]
+    }
+}
+
+fn cheatcodes_many_senders_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Cheatcodes Many Senders Test");
+
+        let token_address = context.deploy_test_contract().await?;
+        let token: Address = token_address.parse()?;
+        let anvil = &context.anvil;
+        let provider = ProviderBuilder::new().on_http(anvil.rpc_url.parse()?);
+
+        // Named accounts are funded on first use and stable afterwards
+        let mut senders = Vec::new();
+        for name in ["alice", "bob", "carol"] {
+            let account = anvil.account(name).await?;
+            if anvil.account(name).await? != account || TestAccount::named(name) != account {
+                return Err(anyhow::anyhow!("Account {} changed between lookups", name));
+            }
+            if provider.get_balance(account.address).await?.is_zero() {
+                return Err(anyhow::anyhow!("Account {} ({}) was not funded", name, account.address));
+            }
+            anvil.set_storage_at(token, balance_slot(account.address), U256::from(1_000u64)).await?;
+            senders.push(account);
+        }
+
+        let alice = senders[0].address;
+        anvil.set_nonce(alice, 42).await?;
+        let nonce = provider.get_transaction_count(alice).await?;
+        if nonce != 42 {
+            return Err(anyhow::anyhow!("alice has nonce {} after anvil_setNonce 42", nonce));
+        }
+
+        for (i, sender) in senders.iter().enumerate() {
+            send_transfer(anvil, sender, token, recipient(i as u8), 100).await?;
+        }
+
+        // An address nobody has the key for sends through impersonation
+        let whale = Address::repeat_byte(0x77);
+        anvil.set_balance(whale, U256::from(10u64).pow(U256::from(18u64))).await?;
+        anvil.set_storage_at(token, balance_slot(whale), U256::from(500u64)).await?;
+        anvil.impersonate(whale).await?;
+        let request = serde_json::json!({
+            "from": whale,
+            "to": token,
+            "data": transfer_calldata(recipient(9), 50),
+            "gas": "0x186a0"
+        });
+        let tx_hash = anvil.send_unsigned(request.clone()).await?;
+        let receipt = provider.get_transaction_receipt(tx_hash.parse()?).await?
+            .ok_or_else(|| anyhow::anyhow!("No receipt for impersonated transfer {}", tx_hash))?;
+        if !receipt.status() {
+            return Err(anyhow::anyhow!("Impersonated transfer {} reverted", tx_hash));
+        }
+        anvil.stop_impersonating(whale).await?;
+        if anvil.send_unsigned(request).await.is_ok() {
+            return Err(anyhow::anyhow!("Sent from {} after impersonation stopped", whale));
+        }
+
+        // Copied runtime code behaves as the same contract at another address
+        let code = provider.get_code_at(token).await?;
+        let clone = Address::repeat_byte(0xc0);
+        anvil.set_code(clone, &code).await?;
+        if provider.get_code_at(clone).await? != code {
+            return Err(anyhow::anyhow!("anvil_setCode did not install the token code at {}", clone));
+        }
+
+        // Timestamps and block numbers under test control
+        let head = anvil.get_block_number().await?;
+        let target = anvil.get_block_timestamp(head).await? + 86_400;
+        anvil.set_next_block_timestamp(target).await?;
+        anvil.mine_n(1).await?;
+        let stamped = anvil.get_block_timestamp(head + 1).await?;
+        if stamped != target {
+            return Err(anyhow::anyhow!("Block {} has timestamp {}, expected {}", head + 1, stamped, target));
+        }
+        anvil.increase_time(3_600).await?;
+        anvil.mine_n(1).await?;
+        let later = anvil.get_block_timestamp(head + 2).await?;
+        if later < target + 3_600 {
+            return Err(anyhow::anyhow!("Block {} has timestamp {}, expected at least {}", head + 2, later, target + 3_600));
+        }
+        anvil.mine_n(5).await?;
+        let mined = anvil.get_block_number().await?;
+        if mined != head + 7 {
+            return Err(anyhow::anyhow!("Chain is at block {}, expected {}", mined, head + 7));
+        }
+
+        let config = context.create_contract_config(&token_address);
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(30).await?;
+
+        // The mint, one transfer per named account and the impersonated one
+        let indexed_senders = read_csv_senders(context)?;
+        let expected: Vec<Address> = senders.iter().map(|s| s.address).chain([Address::ZERO, whale]).collect();
+        let missing: Vec<&Address> = expected.iter()
+            .filter(|a| !indexed_senders.contains(&a.to_string().to_lowercase()))
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!("No indexed Transfer from {:?}; indexed senders: {:?}", missing, indexed_senders));
+        }
+
+        info!("✓ Cheatcodes Many Senders Test PASSED: {} distinct senders indexed", indexed_senders.len());
+        Ok(())
+    })
+}
+
+/// Storage slot of `balanceOf[holder]`
+fn balance_slot(holder: Address) -> U256 {
+    let mut key = [0u8; 64];
+    key[12..32].copy_from_slice(holder.as_slice());
+    key[63] = BALANCE_OF_SLOT;
+    U256::from_be_bytes(keccak256(key).0)
+}
+
+fn recipient(n: u8) -> Address {
+    let mut bytes = [0u8; 20];
+    bytes[0] = 0x5e;
+    bytes[19] = n;
+    Address::from(bytes)
+}
+
+/// `transfer(address,uint256)` calldata
+fn transfer_calldata(to: Address, amount: u64) -> Bytes {
+    let mut data = vec![0xa9, 0x05, 0x9c, 0xbb];
+    data.extend_from_slice(&[0u8; 12]);
+    data.extend_from_slice(to.as_slice());
+    data.extend_from_slice(&U256::from(amount).to_be_bytes::<32>());
+    data.into()
+}
+
+async fn send_transfer(anvil: &AnvilInstance, sender: &TestAccount, token: Address, to: Address, amount: u64) -> Result<()> {
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(sender.signer()))
+        .on_http(anvil.rpc_url.parse()?);
+    let tx = TransactionRequest::default()
+        .to(token)
+        .input(TransactionInput::new(transfer_calldata(to, amount)));
+    let receipt = provider.send_transaction(tx).await?.get_receipt().await?;
+    if !receipt.status() {
+        return Err(anyhow::anyhow!("Transfer from {} reverted", sender.name));
+    }
+    Ok(())
+}
+
+/// Lowercase `from` addresses of every indexed Transfer
+fn read_csv_senders(context: &TestContext) -> Result<HashSet<String>> {
+    let path = context.get_csv_output_path().join("SimpleERC20").join("simpleerc20-transfer.csv");
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let mut lines = content.lines();
+    let headers: Vec<&str> = lines.next().unwrap_or("").split(',').collect();
+    let from_idx = headers.iter().position(|h| *h == "from")
+        .ok_or_else(|| anyhow::anyhow!("from column not found in {}", path.display()))?;
+    Ok(lines
+        .filter_map(|line| line.split(',').nth(from_idx))
+        .map(|from| from.trim().to_lowercase())
+        .collect())
+}
@@ -0,0 +1,320 @@
+use anyhow::{Result, Context};
+use tracing::info;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::future::Future;
+
+use crate::rindexer_cli::{CodegenTarget, NewProjectOptions, StorageChoice};
+use crate::rindexer_client::postgres_env;
+use crate::test_suite::TestContext;
+use crate::tests::capabilities::Capability;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Rocket Pool rETH on mainnet, also used by the direct RPC tests
+const RETH_ADDRESS: &str = "0xae78736cd615f374d3085123a210448e74fc6393";
+
+pub struct CliCommandTests;
+
+impl TestModule for CliCommandTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_cli_new_no_code_csv",
+                "rindexer new no-code with CSV storage creates the project layout and YAML",
+                cli_new_no_code_csv_test,
+            ).with_timeout(60),
+            TestDefinition::new(
+                "test_cli_new_storage_choices",
+                "rindexer new no-code writes the storage section and Docker files for every storage answer",
+                cli_new_storage_choices_test,
+            ).with_timeout(120),
+            TestDefinition::new(
+                "test_cli_delete",
+                "rindexer delete removes indexed CSV data but keeps the project",
+                cli_delete_test,
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"]),
+            TestDefinition::new(
+                "test_cli_start_graphql",
+                "rindexer start graphql serves an already indexed Postgres database",
+                cli_start_graphql_test,
+            ).with_timeout(180)
+             .with_tags(&[TestTag::Graphql, TestTag::Postgres, TestTag::Slow])
+             .requires(&[Capability::Docker])
+             .deploys(&["SimpleERC20"]),
+            TestDefinition::new(
+                "test_cli_codegen_graphql",
+                "rindexer codegen graphql generates typings from a running GraphQL endpoint",
+                cli_codegen_graphql_test,
+            ).with_timeout(180)
+             .with_tags(&[TestTag::Graphql, TestTag::Postgres, TestTag::Slow])
+             .requires(&[Capability::Docker])
+             .deploys(&["SimpleERC20"]),
+            TestDefinition::new(
+                "test_cli_add_contract",
+                "rindexer add contract downloads the ABI and registers the contract in YAML",
+                cli_add_contract_test,
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Network])
+             .requires(&[Capability::EnvVar("MAINNET_RPC_URL")]),
+        ]
+    }
+}
+
+fn cli_new_no_code_csv_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running CLI New (CSV) Test");
+
+        let options = NewProjectOptions::new("cli_csv_project", StorageChoice::Csv)
+            .with_description("Created by the e2e suite");
+        let project = create_project(context, &options).await?;
+
+        let yaml = read_yaml(&project.join("rindexer.yaml"))?;
+        expect_str(&yaml["name"], "cli_csv_project", "name")?;
+        expect_str(&yaml["project_type"], "no-code", "project_type")?;
+        expect_str(&yaml["description"], "Created by the e2e suite", "description")?;
+        if yaml["storage"]["csv"]["enabled"].as_bool() != Some(true) {
+            return Err(anyhow::anyhow!("CSV storage should be enabled: {:?}", yaml["storage"]));
+        }
+        if yaml["storage"]["postgres"]["enabled"].as_bool() == Some(true) {
+            return Err(anyhow::anyhow!("Postgres storage should not be enabled: {:?}", yaml["storage"]));
+        }
+        if project.join("docker-compose.yml").exists() {
+            return Err(anyhow::anyhow!("CSV-only project should not get a docker-compose.yml"));
+        }
+
+        info!("✓ CLI New (CSV) Test PASSED");
+        Ok(())
+    })
+}
+
+fn cli_new_storage_choices_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running CLI New Storage Choices Test");
+
+        for (name, storage) in [
+            ("cli_storage_postgres", StorageChoice::Postgres),
+            ("cli_storage_csv", StorageChoice::Csv),
+            ("cli_storage_both", StorageChoice::Both),
+            ("cli_storage_none", StorageChoice::None),
+        ] {
+            let options = NewProjectOptions::new(name, storage).with_postgres_docker(true);
+            let project = create_project(context, &options).await?;
+            let yaml = read_yaml(&project.join("rindexer.yaml"))?;
+
+            let postgres = yaml["storage"]["postgres"]["enabled"].as_bool().unwrap_or(false);
+            let csv = yaml["storage"]["csv"]["enabled"].as_bool().unwrap_or(false);
+            if postgres != storage.has_postgres() || csv != storage.has_csv() {
+                return Err(anyhow::anyhow!(
+                    "{:?}: expected postgres={} csv={}, YAML has postgres={} csv={}",
+                    storage, storage.has_postgres(), storage.has_csv(), postgres, csv
+                ));
+            }
+
+            // Docker support is only offered (and accepted here) when Postgres is enabled
+            for file in ["docker-compose.yml", ".env"] {
+                if project.join(file).exists() != storage.has_postgres() {
+                    return Err(anyhow::anyhow!(
+                        "{:?}: {} {} expected",
+                        storage, file, if storage.has_postgres() { "is" } else { "is not" }
+                    ));
+                }
+            }
+        }
+
+        info!("✓ CLI New Storage Choices Test PASSED");
+        Ok(())
+    })
+}
+
+fn cli_delete_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running CLI Delete Test");
+
+        let contract_address = context.deploy_test_contract().await?;
+        let config = context.create_contract_config(&contract_address);
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(20).await?;
+        if let Some(mut rindexer) = context.rindexer.take() {
+            rindexer.stop().await?;
+        }
+
+        if context.get_event_count()? == 0 {
+            return Err(anyhow::anyhow!("Expected indexed CSV data before delete"));
+        }
+
+        context.cli().delete(&context.project_path).run().await?.expect_success()?;
+
+        if context.get_event_count()? != 0 {
+            return Err(anyhow::anyhow!("CSV data still present after rindexer delete"));
+        }
+        if !context.project_path.join("rindexer.yaml").exists() {
+            return Err(anyhow::anyhow!("rindexer delete removed the project configuration"));
+        }
+
+        info!("✓ CLI Delete Test PASSED");
+        Ok(())
+    })
+}
+
+fn cli_start_graphql_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running CLI Start GraphQL Test");
+
+        let gql_url = start_graphql_over_indexed_postgres(context).await?;
+
+        let response = reqwest::Client::new()
+            .post(&gql_url)
+            .json(&serde_json::json!({ "query": "{ __typename }" }))
+            .send()
+            .await
+            .context("GraphQL endpoint not reachable")?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("GraphQL returned HTTP {}", response.status()));
+        }
+        let body: serde_json::Value = response.json().await?;
+        if body.get("data").is_none() {
+            return Err(anyhow::anyhow!("GraphQL response has no data: {}", body));
+        }
+
+        info!("✓ CLI Start GraphQL Test PASSED: {} answered", gql_url);
+        Ok(())
+    })
+}
+
+fn cli_codegen_graphql_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running CLI Codegen GraphQL Test");
+
+        let gql_url = start_graphql_over_indexed_postgres(context).await?;
+
+        let before = list_files(&context.project_path)?;
+        context.cli()
+            .codegen(&context.project_path, &CodegenTarget::Graphql { endpoint: gql_url })
+            .run().await?
+            .expect_success()?;
+        let after = list_files(&context.project_path)?;
+
+        let generated: Vec<&PathBuf> = after.difference(&before).collect();
+        if generated.is_empty() {
+            return Err(anyhow::anyhow!("rindexer codegen graphql did not generate any files"));
+        }
+
+        info!("✓ CLI Codegen GraphQL Test PASSED: generated {:?}", generated);
+        Ok(())
+    })
+}
+
+fn cli_add_contract_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running CLI Add Contract Test");
+
+        let rpc_url = std::env::var("MAINNET_RPC_URL").context("MAINNET_RPC_URL not set")?;
+        let options = NewProjectOptions::new("cli_add_contract", StorageChoice::Csv);
+        let project = create_project(context, &options).await?;
+
+        // Point the generated project at mainnet so the contract can be looked up
+        let yaml_path = project.join("rindexer.yaml");
+        let mut yaml = read_yaml(&yaml_path)?;
+        yaml["networks"] = serde_yaml::to_value(vec![serde_json::json!({
+            "name": "ethereum",
+            "chain_id": 1,
+            "rpc": rpc_url,
+        })])?;
+        yaml["contracts"] = serde_yaml::Value::Sequence(Vec::new());
+        std::fs::write(&yaml_path, serde_yaml::to_string(&yaml)?)?;
+        let abis_before = list_files(&project.join("abis"))?;
+
+        context.cli().add_contract(&project, "ethereum", RETH_ADDRESS).run().await?.expect_success()?;
+
+        let yaml = read_yaml(&yaml_path)?;
+        let registered = yaml["contracts"].as_sequence().into_iter().flatten().any(|contract| {
+            contract["details"].as_sequence().into_iter().flatten().any(|detail| {
+                detail["address"].as_str().map(|a| a.eq_ignore_ascii_case(RETH_ADDRESS)).unwrap_or(false)
+            })
+        });
+        if !registered {
+            return Err(anyhow::anyhow!("Contract {} not registered in rindexer.yaml: {:?}", RETH_ADDRESS, yaml["contracts"]));
+        }
+        if list_files(&project.join("abis"))?.len() <= abis_before.len() {
+            return Err(anyhow::anyhow!("rindexer add contract did not download an ABI"));
+        }
+
+        info!("✓ CLI Add Contract Test PASSED");
+        Ok(())
+    })
+}
+
+/// Run `rindexer new no-code` inside the test's project directory and check the basic layout
+async fn create_project(context: &TestContext, options: &NewProjectOptions) -> Result<PathBuf> {
+    context.cli().new_no_code(&context.project_path, options).run().await?.expect_success()?;
+
+    let project = context.project_path.join(&options.name);
+    if !project.join("rindexer.yaml").exists() {
+        return Err(anyhow::anyhow!("rindexer new did not create {}/rindexer.yaml", project.display()));
+    }
+    if !project.join("abis").is_dir() {
+        return Err(anyhow::anyhow!("rindexer new did not create {}/abis", project.display()));
+    }
+    Ok(project)
+}
+
+/// Index the test contract into Postgres, stop the indexer and serve the data
+/// with `rindexer start graphql`; returns the GraphQL endpoint
+async fn start_graphql_over_indexed_postgres(context: &mut TestContext) -> Result<String> {
+    let pg_port = context.start_postgres().await?;
+    let contract_address = context.deploy_test_contract().await?;
+    let mut config = context.create_contract_config(&contract_address);
+    config.storage.postgres.enabled = true;
+    config.storage.csv.enabled = false;
+
+    context.start_rindexer_with_env(config, postgres_env(pg_port)).await?;
+    context.wait_for_sync_completion(60).await?;
+    if let Some(mut rindexer) = context.rindexer.take() {
+        rindexer.stop().await?;
+    }
+
+    let graphql = context.cli()
+        .with_postgres(pg_port)
+        .start_graphql(&context.project_path, context.ports.graphql)
+        .await?;
+    let url = graphql.wait_for_graphql_url(20).await
+        .unwrap_or_else(|| context.graphql_url());
+    context.graphql = Some(graphql);
+    Ok(url)
+}
+
+fn read_yaml(path: &Path) -> Result<serde_yaml::Value> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_yaml::from_str(&content).with_context(|| format!("Invalid YAML in {}", path.display()))
+}
+
+fn expect_str(value: &serde_yaml::Value, expected: &str, field: &str) -> Result<()> {
+    if value.as_str() != Some(expected) {
+        return Err(anyhow::anyhow!("Expected {} '{}', got {:?}", field, expected, value));
+    }
+    Ok(())
+}
+
+/// Every file under `dir`, relative to it
+fn list_files(dir: &Path) -> Result<BTreeSet<PathBuf>> {
+    let mut files = BTreeSet::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                files.insert(relative.to_path_buf());
+            }
+        }
+    }
+    Ok(files)
+}
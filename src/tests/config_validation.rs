@@ -20,12 +20,13 @@ impl TestModule for ConfigValidationTests {
                 "test_missing_abi_path_fails",
                 "Missing ABI path for contract yields actionable error",
                 missing_abi_path_fails_test,
-            ).with_timeout(90),
+            ).with_timeout(90)
+             .deploys(&["SimpleERC20"]),
         ]
     }
 }
 
-fn invalid_yaml_fails_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn invalid_yaml_fails_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Invalid YAML Failure Test");
 
@@ -36,7 +37,7 @@ fn invalid_yaml_fails_test(context: &mut TestContext) -> Pin<Box<dyn Future<Outp
         std::fs::write(&target_yaml_path, bad_yaml)?;
 
         // Attempt to start the indexer; it should fail
-        let mut r = crate::rindexer_client::RindexerInstance::new(&context.rindexer_binary, context.project_path.clone());
+        let mut r = context.new_rindexer_instance();
         let res = r.start_indexer().await;
         if res.is_ok() {
             return Err(anyhow::anyhow!("Indexer unexpectedly started with invalid YAML"));
@@ -47,7 +48,7 @@ fn invalid_yaml_fails_test(context: &mut TestContext) -> Pin<Box<dyn Future<Outp
     })
 }
 
-fn missing_abi_path_fails_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn missing_abi_path_fails_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         // Create a valid contract config but point ABI to a non-existent file
         let contract_address = context.deploy_test_contract().await?;
@@ -61,7 +62,7 @@ fn missing_abi_path_fails_test(context: &mut TestContext) -> Pin<Box<dyn Future<
         let yaml = serde_yaml::to_string(&config)?;
         std::fs::write(&target_yaml_path, yaml)?;
 
-        let mut r = crate::rindexer_client::RindexerInstance::new(&context.rindexer_binary, context.project_path.clone());
+        let mut r = context.new_rindexer_instance();
         let res = r.start_indexer().await;
         if res.is_ok() {
             return Err(anyhow::anyhow!("Indexer unexpectedly started with missing ABI path"));
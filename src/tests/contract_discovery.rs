@@ -4,7 +4,7 @@ use std::pin::Pin;
 use std::future::Future;
 
 use crate::test_suite::TestContext;
-use crate::tests::registry::{TestDefinition, TestModule};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
 
 pub struct ContractDiscoveryTests;
 
@@ -15,12 +15,14 @@ impl TestModule for ContractDiscoveryTests {
                 "test_2_contract_discovery",
                 "Test Rindexer can discover and register contract events from ABI",
                 contract_discovery_test,
-            ).with_timeout(120),
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"]),
         ]
     }
 }
 
-fn contract_discovery_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn contract_discovery_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Test 2: Contract Discovery Test");
         
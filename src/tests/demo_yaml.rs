@@ -4,7 +4,7 @@ use std::pin::Pin;
 use std::future::Future;
 
 use crate::test_suite::TestContext;
-use crate::tests::registry::{TestDefinition, TestModule};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
 
 pub struct DemoYamlTests;
 
@@ -15,12 +15,13 @@ impl TestModule for DemoYamlTests {
                 "test_6_demo_yaml",
                 "Test Rindexer with the demo YAML configuration adapted for Anvil",
                 demo_yaml_test,
-            ).with_timeout(180),
+            ).with_timeout(180)
+             .with_tags(&[TestTag::Historic]),
         ]
     }
 }
 
-fn demo_yaml_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn demo_yaml_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Test 6: Demo YAML Test");
     
@@ -67,7 +68,7 @@ fn demo_yaml_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Resu
         
         // Start Rindexer with the demo configuration
         info!("Starting Rindexer with demo configuration...");
-        let mut rindexer = crate::rindexer_client::RindexerInstance::new(&context.rindexer_binary, context.project_path.clone());
+        let mut rindexer = context.new_rindexer_instance();
         rindexer.start_indexer().await
             .context("Failed to start Rindexer instance")?;
         
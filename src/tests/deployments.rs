@@ -0,0 +1,82 @@
+use anyhow::Result;
+use alloy::dyn_abi::DynSolValue;
+use alloy::primitives::U256;
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+
+use crate::anvil_setup::TRANSFER_TOPIC;
+use crate::contracts::ArtifactSource;
+use crate::test_suite::TestContext;
+use crate::tests::capabilities::Capability;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+pub struct DeploymentTests;
+
+impl TestModule for DeploymentTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_artifact_deployment",
+                "SimpleERC20 deploys through alloy from its checked-in artifact, without forge, and rindexer indexes its mint",
+                artifact_deployment_test,
+            ).with_timeout(60)
+             .with_tags(&[TestTag::Historic])
+             .requires(&[Capability::Artifact("SimpleERC20")]),
+        ]
+    }
+}
+
+fn artifact_deployment_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Artifact Deployment Test");
+
+        let deployment = context.deploy_contract("SimpleERC20", &[]).await?;
+        if deployment.source != ArtifactSource::CheckedIn {
+            return Err(anyhow::anyhow!("SimpleERC20 was deployed from {:?}, not its checked-in artifact", deployment.source));
+        }
+
+        // The constructor mints the supply to the deployer in the creation transaction
+        let logs = context.anvil.get_logs(&deployment.address, TRANSFER_TOPIC).await?;
+        let mint = match logs.as_slice() {
+            [mint] => mint,
+            _ => return Err(anyhow::anyhow!("Expected one mint Transfer from {}, found {}", deployment.address, logs.len())),
+        };
+        if mint.tx_hash != deployment.tx_hash.to_lowercase() || mint.block_number != deployment.block_number {
+            return Err(anyhow::anyhow!(
+                "Mint was logged in {} (block {}), deployment reported {} (block {})",
+                mint.tx_hash, mint.block_number, deployment.tx_hash, deployment.block_number
+            ));
+        }
+
+        // Each deployment is a new contract
+        let second = context.deploy_contract("SimpleERC20", &[]).await?;
+        if second.address == deployment.address {
+            return Err(anyhow::anyhow!("Second deployment reused address {}", second.address));
+        }
+
+        // SimpleERC20's constructor takes no arguments
+        let extra = [DynSolValue::Uint(U256::from(1u64), 256)];
+        if context.deploy_contract("SimpleERC20", &extra).await.is_ok() {
+            return Err(anyhow::anyhow!("Deployment with an unexpected constructor argument succeeded"));
+        }
+        if context.deploy_contract("NoSuchContract", &[]).await.is_ok() {
+            return Err(anyhow::anyhow!("Deployment of a contract with no artifact or source succeeded"));
+        }
+
+        let config = context.create_contract_config(&deployment.address);
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(30).await?;
+
+        let indexed = context.get_event_count()?;
+        if indexed == 0 {
+            return Err(anyhow::anyhow!("The mint Transfer of {} was not indexed", deployment.address));
+        }
+
+        info!(
+            "✓ Artifact Deployment Test PASSED: deployed at {} in block {}, {} events indexed",
+            deployment.address, deployment.block_number, indexed
+        );
+        Ok(())
+    })
+}
@@ -4,7 +4,11 @@ use std::pin::Pin;
 use std::future::Future;
 
 use crate::test_suite::TestContext;
-use crate::tests::registry::{TestDefinition, TestModule};
+use crate::tests::capabilities::Capability;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Mainnet traffic of the whole rETH range in the expected CSV, under `data/cassettes`
+const CASSETTE: &str = "direct-rpc-reth";
 
 pub struct DirectRpcTests;
 
@@ -15,22 +19,19 @@ impl TestModule for DirectRpcTests {
                 "test_direct_rpc",
                 "Direct RPC realism: Rocket Pool rETH Transfer vs expected CSV",
                 direct_rpc_test,
-            ).with_timeout(900),
+            ).with_timeout(900)
+             .with_tags(&[TestTag::Network, TestTag::Historic, TestTag::Slow])
+             .requires(&[Capability::Cassette { name: CASSETTE, upstream_env: "MAINNET_RPC_URL" }]),
         ]
     }
 }
 
-fn direct_rpc_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn direct_rpc_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Test 9: Direct RPC Realism (ERC20 Transfer)");
 
-        // Require MAINNET_RPC_URL in env; skip cleanly if missing
-        let mainnet_rpc = match std::env::var("MAINNET_RPC_URL") {
-            Ok(v) if !v.trim().is_empty() => v,
-            _ => {
-                return Err(crate::tests::test_runner::SkipTest("MAINNET_RPC_URL not set; skipping direct RPC test".to_string()).into());
-            }
-        };
+        // Replays the cassette offline, or goes to MAINNET_RPC_URL (Capability::Cassette)
+        let mainnet = context.start_cassette_proxy(CASSETTE, "MAINNET_RPC_URL").await?;
 
         // Expected CSV file (default to provided rETH sample)
         let expected_csv = std::env::var("DIRECT_RPC_EXPECTED_CSV")
@@ -47,7 +48,7 @@ fn direct_rpc_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Res
         let end_block = Some(end_block_inclusive);
 
         // Build config targeting MAINNET directly
-        let config = build_direct_rpc_config(&mainnet_rpc, &contract_address, start_block, end_block);
+        let config = build_direct_rpc_config(&mainnet.url, &contract_address, start_block, end_block);
         context.start_rindexer(config).await?;
 
         // Wait until historical indexing completes by log (configurable)
@@ -70,6 +71,9 @@ fn direct_rpc_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Res
             warn!("CSV mismatch: expected {} rows, produced {} rows", expected_hashes.len(), produced_hashes.len());
             return Err(anyhow::anyhow!("Produced CSV does not match expected CSV"));
         }
+        if mainnet.is_recording() {
+            mainnet.save_cassette()?;
+        }
 
         info!("✓ Test 9 PASSED: Direct RPC Transfer matched expected CSV");
         Ok(())
@@ -0,0 +1,165 @@
+use anyhow::Result;
+use tracing::info;
+use std::collections::HashSet;
+use std::pin::Pin;
+use std::future::Future;
+
+use crate::anvil_setup::{erc20_transfer_request, AnvilInstance, AnvilOptions, ChainLog, Mining, TRANSFER_TOPIC};
+use crate::test_suite::TestContext;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Chain id of the fork, distinct from the parent's 31337
+const FORK_CHAIN_ID: u64 = 31339;
+
+/// Transfers on the parent up to and including the fork block
+const PARENT_TRANSFERS: u64 = 3;
+/// Transfers on the parent after the fork block; the fork must never see them
+const PARENT_ONLY_TRANSFERS: u64 = 2;
+/// Transfers mined on the fork after the fork block
+const FORK_TRANSFERS: u64 = 4;
+
+pub struct ForkedAnvilTests;
+
+impl TestModule for ForkedAnvilTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_fork_only_events",
+                "Indexing a local fork from the fork block on finds only the fork's own Transfers",
+                fork_only_test,
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_mining(Mining::Auto)),
+            TestDefinition::new(
+                "test_fork_parent_events",
+                "Indexing a local fork up to the fork block finds the parent's Transfers served through the fork",
+                parent_only_test,
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_mining(Mining::Auto)),
+            TestDefinition::new(
+                "test_fork_across_fork_block",
+                "Indexing a local fork across the fork block finds parent and fork Transfers but not the parent's later ones",
+                across_fork_block_test,
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_mining(Mining::Auto)),
+        ]
+    }
+}
+
+/// Which blocks of the fork rindexer is pointed at
+#[derive(Debug, Clone, Copy)]
+enum ForkRange {
+    /// After the fork block
+    ForkOnly,
+    /// Up to and including the fork block
+    ParentOnly,
+    /// From genesis to the fork's head
+    AcrossForkBlock,
+}
+
+fn fork_only_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(index_fork(context, ForkRange::ForkOnly))
+}
+
+fn parent_only_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(index_fork(context, ForkRange::ParentOnly))
+}
+
+fn across_fork_block_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(index_fork(context, ForkRange::AcrossForkBlock))
+}
+
+async fn index_fork(context: &mut TestContext, range: ForkRange) -> Result<()> {
+    info!("Running Forked Anvil {:?} Test", range);
+
+    let contract_address = context.deploy_test_contract().await?;
+    send_transfers(&context.anvil, &contract_address, PARENT_TRANSFERS).await?;
+    let fork_block = context.anvil.get_block_number().await?;
+    send_transfers(&context.anvil, &contract_address, PARENT_ONLY_TRANSFERS).await?;
+
+    context.start_fork(
+        "fork",
+        fork_block,
+        AnvilOptions::new().with_chain_id(FORK_CHAIN_ID).with_mining(Mining::Auto),
+    ).await?;
+    let fork = context.anvil_for("fork")?;
+    send_transfers(fork, &contract_address, FORK_TRANSFERS).await?;
+
+    let parent_logs = context.anvil.get_logs(&contract_address, TRANSFER_TOPIC).await?;
+    let fork_logs = fork.get_logs(&contract_address, TRANSFER_TOPIC).await?;
+    check_fork_history(&parent_logs, &fork_logs, fork_block)?;
+
+    let (start, end) = match range {
+        ForkRange::ForkOnly => (fork_block + 1, None),
+        ForkRange::ParentOnly => (0, Some(fork_block)),
+        ForkRange::AcrossForkBlock => (0, None),
+    };
+    let expected: Vec<ChainLog> = fork_logs.iter()
+        .filter(|log| (start..=end.unwrap_or(u64::MAX)).contains(&log.block_number))
+        .cloned()
+        .collect();
+
+    let mut config = context.create_contract_config(&contract_address);
+    config.networks[0].rpc = fork.rpc_url.clone();
+    config.networks[0].chain_id = fork.chain_id;
+    let detail = &mut config.contracts[0].details[0];
+    detail.start_block = start.to_string();
+    detail.end_block = end.map(|end| end.to_string());
+
+    context.start_rindexer(config).await?;
+    context.wait_for_sync_completion(60).await?;
+    let indexed = context.stop_and_compare(&expected).await?;
+
+    info!(
+        "✓ Forked Anvil {:?} Test PASSED: {} Transfers indexed from the fork of block {}",
+        range, indexed.len(), fork_block
+    );
+    Ok(())
+}
+
+/// The fork must share the parent's logs up to the fork block and none after it
+fn check_fork_history(parent_logs: &[ChainLog], fork_logs: &[ChainLog], fork_block: u64) -> Result<()> {
+    let shared: Vec<&ChainLog> = parent_logs.iter().filter(|log| log.block_number <= fork_block).collect();
+    let inherited: Vec<&ChainLog> = fork_logs.iter().filter(|log| log.block_number <= fork_block).collect();
+    if shared != inherited {
+        return Err(anyhow::anyhow!(
+            "Fork does not share the parent's history up to block {}:\nparent: {:?}\nfork: {:?}",
+            fork_block, shared, inherited
+        ));
+    }
+
+    let fork_txs: HashSet<&str> = fork_logs.iter().map(|log| log.tx_hash.as_str()).collect();
+    let leaked: Vec<&ChainLog> = parent_logs.iter()
+        .filter(|log| log.block_number > fork_block && fork_txs.contains(log.tx_hash.as_str()))
+        .collect();
+    if !leaked.is_empty() {
+        return Err(anyhow::anyhow!("Parent Transfers after the fork block appear on the fork: {:?}", leaked));
+    }
+
+    let own = fork_logs.len() - inherited.len();
+    if own as u64 != FORK_TRANSFERS {
+        return Err(anyhow::anyhow!("Fork has {} Transfers after block {}, expected {}", own, fork_block, FORK_TRANSFERS));
+    }
+    Ok(())
+}
+
+/// Send `count` deployer transfers on `anvil`; automine puts each in a block of its own
+async fn send_transfers(anvil: &AnvilInstance, contract_address: &str, count: u64) -> Result<()> {
+    let deployer = anvil.deployer().address.to_string();
+    let start = anvil.get_block_number().await?;
+    for i in 0..count {
+        anvil.send_unsigned(erc20_transfer_request(
+            &deployer,
+            contract_address,
+            // Recipient is unique per chain and block
+            &format!("0xf0{:06x}{:032x}", anvil.chain_id, start + i),
+            1_000 + i,
+        )).await?;
+    }
+    Ok(())
+}
@@ -4,7 +4,7 @@ use std::pin::Pin;
 use std::future::Future;
 
 use crate::test_suite::TestContext;
-use crate::tests::registry::{TestDefinition, TestModule};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
 
 pub struct HealthAssertionsTests;
 
@@ -15,12 +15,14 @@ impl TestModule for HealthAssertionsTests {
                 "test_health_endpoint_ready_and_complete",
                 "Assert /health shows ready and indexing tasks go to 0",
                 health_endpoint_ready_and_complete_test,
-            ).with_timeout(120),
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"]),
         ]
     }
 }
 
-fn health_endpoint_ready_and_complete_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn health_endpoint_ready_and_complete_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Health Endpoint Assertions Test");
 
@@ -5,7 +5,7 @@ use std::pin::Pin;
 use std::future::Future;
 
 use crate::test_suite::TestContext;
-use crate::tests::registry::{TestDefinition, TestModule};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
 
 pub struct HistoricIndexingTests;
 
@@ -16,12 +16,14 @@ impl TestModule for HistoricIndexingTests {
                 "test_3_historic_indexing",
                 "Test Rindexer can index historic events from contract deployment",
                 historic_indexing_test,
-            ).with_timeout(150),
+            ).with_timeout(150)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"]),
         ]
     }
 }
 
-fn historic_indexing_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn historic_indexing_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Test 3: Historic Indexing Test");
     
@@ -37,6 +39,19 @@ fn historic_indexing_test(context: &mut TestContext) -> Pin<Box<dyn Future<Outpu
         // Wait for Rindexer to complete historic indexing using health endpoint
         context.wait_for_sync_completion(20).await?;
         
+        // rindexer must not have panicked along the way, even if it reported completion
+        if let Some(rindexer) = &context.rindexer {
+            if let Some(panic) = rindexer.events.panics().first() {
+                return Err(anyhow::anyhow!("Rindexer panicked during historic indexing: {}", panic));
+            }
+            let ranges = rindexer.events.indexed_ranges(Some("SimpleERC20"), Some("Transfer"));
+            info!(
+                "Rindexer fetched {} block range(s) for SimpleERC20::Transfer, last progress {:?}",
+                ranges.len(),
+                rindexer.events.latest_progress(Some("SimpleERC20"), Some("Transfer"), None),
+            );
+        }
+        
         // Verify CSV file was created and contains the deployment Transfer event
         let csv_path = context.get_csv_output_path().join("SimpleERC20").join("simpleerc20-transfer.csv");
         
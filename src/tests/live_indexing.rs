@@ -26,7 +26,7 @@ impl TestModule for LiveIndexingTests {
     }
 }
 
-fn live_indexing_basic_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn live_indexing_basic_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Live Indexing Test: Basic");
     
@@ -72,7 +72,7 @@ fn live_indexing_basic_test(context: &mut TestContext) -> Pin<Box<dyn Future<Out
     })
 }
 
-fn live_indexing_high_frequency_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn live_indexing_high_frequency_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Live Indexing Test: High Frequency");
     
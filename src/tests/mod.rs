@@ -12,34 +12,102 @@ pub mod postgres_e2e;
 pub mod graphql_queries;
 pub mod direct_rpc;
 pub mod multi_network;
+pub mod shutdown_signals;
+pub mod cli_commands;
+pub mod anvil_options;
+pub mod reorg;
+pub mod deployments;
+pub mod cheatcodes;
+pub mod backfill;
+pub mod sparse_history;
+pub mod forked_anvil;
+pub mod rpc_faults;
+pub mod provider_limits;
+pub mod rpc_cassettes;
+pub mod ws_transport;
 
 // Registry and runner
 pub mod registry;
 pub mod test_runner;
 pub mod test_suite;
+pub mod report;
+pub mod capabilities;
 
 // Legacy test modules removed - now using registry-based system
 
 use anyhow::Result;
+use std::path::PathBuf;
+use crate::tests::registry::TestSelection;
+use crate::tests::report::{write_reports, ReportSpec};
+use crate::process_group::reap_all_groups;
+use crate::docker::remove_run_containers;
+use tracing::error;
 use crate::tests::test_runner::{TestRunner, TestRunnerConfig};
 
 // Legacy test system removed - now using registry-based system
 
+/// Options for a registry-based test run, as parsed from the command line
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub rindexer_binary: String,
+    pub selection: TestSelection,
+    pub jobs: usize,
+    pub reports: Vec<ReportSpec>,
+    /// Where failure bundles go; `None` uses the runner default
+    pub artifacts_dir: Option<PathBuf>,
+    pub keep_temp: bool,
+    /// Where per-test process logs go; `None` uses the runner default
+    pub logs_dir: Option<PathBuf>,
+}
+
+/// Kill any process group still alive when the runner exits
+fn reap_at_exit() {
+    for group in reap_all_groups() {
+        error!("Killed {} left running at exit", group);
+    }
+}
+
 /// Registry-based test runner
-pub async fn run_tests(rindexer_binary: String, test_names: Option<Vec<String>>) -> Result<()> {
-    let config = TestRunnerConfig {
-        rindexer_binary,
-        anvil_port: 8545,
-        health_port: 8080,
+pub async fn run_tests(options: RunOptions) -> Result<()> {
+    let mut config = TestRunnerConfig {
+        rindexer_binary: options.rindexer_binary,
+        jobs: options.jobs.max(1),
+        keep_temp: options.keep_temp,
+        ..TestRunnerConfig::default()
     };
+    if let Some(artifacts_dir) = options.artifacts_dir {
+        config.artifacts_dir = artifacts_dir;
+    }
+    if let Some(logs_dir) = options.logs_dir {
+        config.logs_dir = logs_dir;
+    }
 
     let runner = TestRunner::new(config);
 
-    let suite = if let Some(names) = test_names {
-        runner.run_filtered_tests(&names).await?
-    } else {
-        runner.run_all_tests().await?
+    let run = async {
+        if options.selection.is_empty() {
+            runner.run_all_tests().await
+        } else {
+            runner.run_selected_tests(&options.selection).await
+        }
     };
+    // Ctrl-C skips teardown, so kill everything the run started before exiting
+    let suite = tokio::select! {
+        suite = run => {
+            reap_at_exit();
+            suite?
+        }
+        _ = tokio::signal::ctrl_c() => {
+            error!("Interrupted, killing every process group and container started by this run");
+            reap_at_exit();
+            for container in remove_run_containers() {
+                error!("Removed Postgres container {}", container);
+            }
+            std::process::exit(130);
+        }
+    };
+
+    write_reports(&suite, &options.reports)?;
 
     let failed_count = suite.failed_count() + suite.timeout_count();
     if failed_count > 0 {
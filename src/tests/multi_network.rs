@@ -3,8 +3,13 @@ use tracing::info;
 use std::pin::Pin;
 use std::future::Future;
 
-use crate::test_suite::TestContext;
-use crate::tests::registry::{TestDefinition, TestModule};
+use crate::anvil_setup::{AnvilInstance, AnvilOptions, ChainLog, Mining, TRANSFER_TOPIC};
+use crate::test_suite::{ContractConfig, ContractDetail, TestContext};
+use crate::tests::capabilities::Capability;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Mainnet traffic of the mixed test's 20 rETH blocks, under `data/cassettes`
+const MIXED_CASSETTE: &str = "multi-network-reth";
 
 pub struct MultiNetworkTests;
 
@@ -15,22 +20,41 @@ impl TestModule for MultiNetworkTests {
                 "test_multi_network_mixed",
                 "Multi-network historic: mainnet rETH + anvil SimpleERC20",
                 multi_network_mixed_test,
-            ).with_timeout(900),
+            ).with_timeout(900)
+             .with_tags(&[TestTag::Network, TestTag::Historic, TestTag::Slow])
+             .requires(&[Capability::Cassette { name: MIXED_CASSETTE, upstream_env: "MAINNET_RPC_URL" }])
+             .deploys(&["SimpleERC20"]),
+            TestDefinition::new(
+                "test_local_multi_network_same_contract",
+                "Same contract on two local chains: one CSV, each row tagged with its chain's network",
+                local_same_contract_test,
+            ).with_timeout(150)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"]),
+            TestDefinition::new(
+                "test_local_multi_network_distinct_contracts",
+                "Different contracts on two local chains each index only their own chain",
+                local_distinct_contracts_test,
+            ).with_timeout(150)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"]),
+            TestDefinition::new(
+                "test_local_multi_network_block_times",
+                "Live indexing across two local chains mining at different block times",
+                local_block_times_test,
+            ).with_timeout(180)
+             .with_tags(&[TestTag::Live, TestTag::Slow])
+             .deploys(&["SimpleERC20"]),
         ]
     }
 }
 
-fn multi_network_mixed_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn multi_network_mixed_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Test Multi-Network: mainnet rETH historic + anvil SimpleERC20 historic");
 
-        // Require MAINNET_RPC_URL
-        let mainnet_rpc = match std::env::var("MAINNET_RPC_URL") {
-            Ok(v) if !v.trim().is_empty() => v,
-            _ => {
-                return Err(crate::tests::test_runner::SkipTest("MAINNET_RPC_URL not set; skipping multi-network test".to_string()).into());
-            }
-        };
+        // Replays the cassette offline, or goes to MAINNET_RPC_URL (Capability::Cassette)
+        let mainnet = context.start_cassette_proxy(MIXED_CASSETTE, "MAINNET_RPC_URL").await?;
 
         // Use a small subset of blocks for multi-network test (not full CSV like direct_rpc)
         // Just test a few blocks to verify multi-network functionality
@@ -53,7 +77,7 @@ fn multi_network_mixed_test(context: &mut TestContext) -> Pin<Box<dyn Future<Out
         // Pre-feed some transfers using direct contract calls
         let num_transfers = 5;
         for i in 0..num_transfers {
-            feed_transfer_on_anvil(&context.anvil.rpc_url, &anvil_contract, i).await?;
+            feed_transfer_on_anvil(&context.anvil, &anvil_contract, i).await?;
             context.anvil.mine_block().await?; // Mine to advance nonce
         }
         
@@ -63,7 +87,7 @@ fn multi_network_mixed_test(context: &mut TestContext) -> Pin<Box<dyn Future<Out
 
         // Build multi-network config: mainnet rETH + anvil SimpleERC20 (both historic)
         let config = build_multi_network_config(
-            &mainnet_rpc,
+            &mainnet.url,
             &context.anvil.rpc_url,
             reth_address,
             &anvil_contract,
@@ -121,6 +145,9 @@ fn multi_network_mixed_test(context: &mut TestContext) -> Pin<Box<dyn Future<Out
             return Err(anyhow::anyhow!("Anvil CSV has {} rows, expected at least {}", anvil_hashes.len(), expected_anvil_count));
         }
         info!("✓ Anvil SimpleERC20 CSV validated ({} rows)", anvil_hashes.len());
+        if mainnet.is_recording() {
+            mainnet.save_cassette()?;
+        }
 
         info!("✓ Test Multi-Network PASSED: mainnet rETH ({} events in 20 blocks) + anvil SimpleERC20 ({} transfers) indexed on separate networks", 
               produced_reth_hashes.len(), expected_anvil_count);
@@ -128,6 +155,7 @@ fn multi_network_mixed_test(context: &mut TestContext) -> Pin<Box<dyn Future<Out
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_multi_network_config(
     mainnet_rpc: &str,
     anvil_rpc: &str,
@@ -183,20 +211,17 @@ fn build_multi_network_config(
     }
 }
 
-async fn feed_transfer_on_anvil(rpc_url: &str, contract_address: &str, nonce: usize) -> Result<()> {
+async fn feed_transfer_on_anvil(anvil: &AnvilInstance, contract_address: &str, nonce: usize) -> Result<()> {
     use alloy::{
         primitives::{Address, U256},
         providers::{Provider, ProviderBuilder},
-        signers::local::PrivateKeySigner,
         network::EthereumWallet,
         rpc::types::TransactionRequest,
     };
     
-    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
-    let signer: PrivateKeySigner = private_key.parse()?;
-    let signer_address = signer.address();
-    let wallet = EthereumWallet::from(signer);
-    let provider = ProviderBuilder::new().wallet(wallet).on_http(rpc_url.parse()?);
+    let sender = anvil.deployer();
+    let wallet = EthereumWallet::from(sender.signer());
+    let provider = ProviderBuilder::new().wallet(wallet).on_http(anvil.rpc_url.parse()?);
     
     let contract_addr: Address = contract_address.parse()?;
     let recipient = generate_test_address(nonce as u64);
@@ -211,8 +236,8 @@ async fn feed_transfer_on_anvil(rpc_url: &str, contract_address: &str, nonce: us
     value_bytes.copy_from_slice(&amount.to_be_bytes::<32>());
     data.extend_from_slice(&value_bytes);
     
-    // Get nonce for the account
-    let tx_nonce = provider.get_transaction_count(signer_address).await?;
+    // Get nonce for the account, counting transfers not mined yet
+    let tx_nonce = provider.get_transaction_count(sender.address).pending().await?;
     
     use alloy::rpc::types::TransactionInput;
     let mut tx = TransactionRequest::default()
@@ -223,7 +248,7 @@ async fn feed_transfer_on_anvil(rpc_url: &str, contract_address: &str, nonce: us
         .max_fee_per_gas(20000000000u128)
         .max_priority_fee_per_gas(1000000000u128);
     
-    tx.chain_id = Some(31337u64);
+    tx.chain_id = Some(anvil.chain_id);
     
     let _pending = provider.send_transaction(tx).await?.register().await?;
     Ok(())
@@ -298,3 +323,267 @@ fn derive_block_range_from_csv(path: &str) -> Result<(u64, u64)> {
     }
 }
 
+
+fn local_same_contract_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Local Multi-Network Same Contract Test");
+
+        context.start_anvil("beta", AnvilOptions::new().with_chain_id(31338)).await?;
+
+        // Same deployer and nonce on both chains gives the same address
+        let alpha_contract = context.deploy_test_contract().await?;
+        let beta_contract = context.deploy_test_contract_on("beta").await?;
+        if !alpha_contract.eq_ignore_ascii_case(&beta_contract) {
+            return Err(anyhow::anyhow!("Expected one address on both chains, got {} and {}", alpha_contract, beta_contract));
+        }
+
+        feed_and_mine(&context.anvil, &alpha_contract, 3).await?;
+        feed_and_mine(context.anvil_for("beta")?, &beta_contract, 2).await?;
+
+        let alpha = &context.anvil;
+        let beta = context.anvil_for("beta")?;
+        let config = local_multi_network_config(
+            &[("anvil", alpha), ("beta", beta)],
+            vec![simple_erc20_contract("SimpleERC20", vec![
+                detail("anvil", &alpha_contract, Some(alpha.get_block_number().await?)),
+                detail("beta", &beta_contract, Some(beta.get_block_number().await?)),
+            ])],
+        );
+        let chains = vec![
+            ("anvil", alpha.get_logs(&alpha_contract, TRANSFER_TOPIC).await?),
+            ("beta", beta.get_logs(&beta_contract, TRANSFER_TOPIC).await?),
+        ];
+
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(60).await?;
+
+        let path = produced_csv_path_for(context, "SimpleERC20", "transfer");
+        let rows = wait_for_rows(&path, chains.iter().map(|(_, logs)| logs.len()).sum(), 60).await?;
+        assert_rows_match_chains(&rows, &chains)?;
+
+        info!("✓ Local Multi-Network Same Contract Test PASSED: {} rows across 2 chains", rows.len());
+        Ok(())
+    })
+}
+
+fn local_distinct_contracts_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Local Multi-Network Distinct Contracts Test");
+
+        context.start_anvil("beta", AnvilOptions::new().with_chain_id(31338)).await?;
+
+        let alpha_contract = context.deploy_test_contract().await?;
+        // Burn the first address on beta so the two contracts differ
+        context.deploy_test_contract_on("beta").await?;
+        let beta_contract = context.deploy_test_contract_on("beta").await?;
+
+        feed_and_mine(&context.anvil, &alpha_contract, 2).await?;
+        feed_and_mine(context.anvil_for("beta")?, &beta_contract, 3).await?;
+
+        let alpha = &context.anvil;
+        let beta = context.anvil_for("beta")?;
+        let config = local_multi_network_config(
+            &[("anvil", alpha), ("beta", beta)],
+            vec![
+                simple_erc20_contract("AlphaToken", vec![
+                    detail("anvil", &alpha_contract, Some(alpha.get_block_number().await?)),
+                ]),
+                simple_erc20_contract("BetaToken", vec![
+                    detail("beta", &beta_contract, Some(beta.get_block_number().await?)),
+                ]),
+            ],
+        );
+        let alpha_chain = vec![("anvil", alpha.get_logs(&alpha_contract, TRANSFER_TOPIC).await?)];
+        let beta_chain = vec![("beta", beta.get_logs(&beta_contract, TRANSFER_TOPIC).await?)];
+
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(60).await?;
+
+        let alpha_rows = wait_for_rows(&produced_csv_path_for(context, "AlphaToken", "transfer"), alpha_chain[0].1.len(), 60).await?;
+        let beta_rows = wait_for_rows(&produced_csv_path_for(context, "BetaToken", "transfer"), beta_chain[0].1.len(), 60).await?;
+        assert_rows_match_chains(&alpha_rows, &alpha_chain).context("AlphaToken")?;
+        assert_rows_match_chains(&beta_rows, &beta_chain).context("BetaToken")?;
+
+        info!(
+            "✓ Local Multi-Network Distinct Contracts Test PASSED: {} AlphaToken rows, {} BetaToken rows",
+            alpha_rows.len(), beta_rows.len()
+        );
+        Ok(())
+    })
+}
+
+fn local_block_times_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Local Multi-Network Block Times Test");
+
+        // The test's own Anvil mines every second, beta every three seconds
+        context.start_anvil(
+            "beta",
+            AnvilOptions::new().with_chain_id(31338).with_mining(Mining::Interval(std::time::Duration::from_secs(3))),
+        ).await?;
+
+        let alpha_contract = context.deploy_test_contract().await?;
+        let beta_contract = context.deploy_test_contract_on("beta").await?;
+
+        let config = {
+            let alpha = &context.anvil;
+            let beta = context.anvil_for("beta")?;
+            local_multi_network_config(
+                &[("anvil", alpha), ("beta", beta)],
+                vec![simple_erc20_contract("SimpleERC20", vec![
+                    detail("anvil", &alpha_contract, None),
+                    detail("beta", &beta_contract, None),
+                ])],
+            )
+        };
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(60).await?;
+
+        let alpha_start = context.anvil.get_block_number().await?;
+        let beta_start = context.anvil_for("beta")?.get_block_number().await?;
+
+        // Transfers are left to each chain's own block time
+        for round in 0..4 {
+            feed_transfer_on_anvil(&context.anvil, &alpha_contract, round).await?;
+            feed_transfer_on_anvil(context.anvil_for("beta")?, &beta_contract, round).await?;
+            tokio::time::sleep(std::time::Duration::from_millis(3500)).await;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(4)).await;
+
+        let alpha = &context.anvil;
+        let beta = context.anvil_for("beta")?;
+        let alpha_blocks = alpha.get_block_number().await? - alpha_start;
+        let beta_blocks = beta.get_block_number().await? - beta_start;
+        if beta_blocks >= alpha_blocks {
+            return Err(anyhow::anyhow!(
+                "beta (3s blocks) mined {} blocks while anvil (1s blocks) mined {}",
+                beta_blocks, alpha_blocks
+            ));
+        }
+
+        let chains = vec![
+            ("anvil", alpha.get_logs(&alpha_contract, TRANSFER_TOPIC).await?),
+            ("beta", beta.get_logs(&beta_contract, TRANSFER_TOPIC).await?),
+        ];
+        let path = produced_csv_path_for(context, "SimpleERC20", "transfer");
+        let rows = wait_for_rows(&path, chains.iter().map(|(_, logs)| logs.len()).sum(), 60).await?;
+        assert_rows_match_chains(&rows, &chains)?;
+
+        info!(
+            "✓ Local Multi-Network Block Times Test PASSED: {} rows, {} anvil blocks vs {} beta blocks",
+            rows.len(), alpha_blocks, beta_blocks
+        );
+        Ok(())
+    })
+}
+
+/// Send `count` transfers on `anvil`, mining each one right away
+async fn feed_and_mine(anvil: &AnvilInstance, contract_address: &str, count: usize) -> Result<()> {
+    for i in 0..count {
+        feed_transfer_on_anvil(anvil, contract_address, i).await?;
+        anvil.mine_block().await?;
+    }
+    Ok(())
+}
+
+fn local_multi_network_config(
+    networks: &[(&str, &AnvilInstance)],
+    contracts: Vec<ContractConfig>,
+) -> crate::test_suite::RindexerConfig {
+    use crate::test_suite::{RindexerConfig, NetworkConfig, StorageConfig, PostgresConfig, CsvConfig, NativeTransfersConfig};
+
+    RindexerConfig {
+        name: "local_multi_network_test".to_string(),
+        project_type: "no-code".to_string(),
+        config: serde_json::json!({}),
+        timestamps: None,
+        networks: networks.iter()
+            .map(|(name, anvil)| NetworkConfig {
+                name: name.to_string(),
+                chain_id: anvil.chain_id,
+                rpc: anvil.rpc_url.clone(),
+            })
+            .collect(),
+        storage: StorageConfig {
+            postgres: PostgresConfig { enabled: false },
+            csv: CsvConfig { enabled: true }
+        },
+        native_transfers: NativeTransfersConfig { enabled: false },
+        contracts,
+    }
+}
+
+fn simple_erc20_contract(name: &str, details: Vec<ContractDetail>) -> ContractConfig {
+    ContractConfig {
+        name: name.to_string(),
+        details,
+        abi: Some("./abis/SimpleERC20.abi.json".to_string()),
+        include_events: Some(vec![crate::test_suite::EventConfig { name: "Transfer".to_string() }]),
+    }
+}
+
+fn detail(network: &str, address: &str, end_block: Option<u64>) -> ContractDetail {
+    ContractDetail {
+        network: network.to_string(),
+        address: address.to_string(),
+        start_block: "0".to_string(),
+        end_block: end_block.map(|b| b.to_string()),
+    }
+}
+
+/// Poll a produced CSV until it has at least `expected` rows, returning
+/// `(tx_hash, network)` for each row
+async fn wait_for_rows(path: &str, expected: usize, timeout_seconds: u64) -> Result<Vec<(String, String)>> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_seconds);
+    loop {
+        let rows = load_networks_from_csv(path).unwrap_or_default();
+        if rows.len() >= expected {
+            return Ok(rows);
+        }
+        if std::time::Instant::now() > deadline {
+            return Err(anyhow::anyhow!("{} has {} rows after {}s, expected {}", path, rows.len(), timeout_seconds, expected));
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+}
+
+fn load_networks_from_csv(path: &str) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Cannot open CSV at {}", path))?;
+    let mut lines = content.lines();
+    let header = lines.next().ok_or_else(|| anyhow::anyhow!("CSV missing header"))?;
+    let headers: Vec<&str> = header.split(',').collect();
+    let tx_idx = headers.iter().position(|h| *h == "tx_hash")
+        .ok_or_else(|| anyhow::anyhow!("tx_hash column not found"))?;
+    let network_idx = headers.iter().position(|h| *h == "network")
+        .ok_or_else(|| anyhow::anyhow!("network column not found"))?;
+
+    Ok(lines
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split(',').collect();
+            Some((cols.get(tx_idx)?.trim().to_lowercase(), cols.get(network_idx)?.trim().to_string()))
+        })
+        .collect())
+}
+
+/// Every row must be a Transfer on the chain its `network` column names,
+/// and every Transfer on those chains must have exactly one row
+fn assert_rows_match_chains(rows: &[(String, String)], chains: &[(&str, Vec<ChainLog>)]) -> Result<()> {
+    let mut expected: Vec<(String, String)> = chains.iter()
+        .flat_map(|(network, logs)| logs.iter().map(move |log| (log.tx_hash.clone(), network.to_string())))
+        .collect();
+    let mut actual = rows.to_vec();
+    expected.sort();
+    actual.sort();
+
+    if expected != actual {
+        let wrong: Vec<_> = actual.iter().filter(|row| !expected.contains(row)).collect();
+        let missing: Vec<_> = expected.iter().filter(|row| !actual.contains(row)).collect();
+        return Err(anyhow::anyhow!(
+            "CSV rows do not match the chains ({} rows, {} expected)\nunexpected (tx_hash, network): {:?}\nmissing: {:?}",
+            actual.len(), expected.len(), wrong, missing
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+
+use crate::anvil_setup::{AnvilOptions, TRANSFER_TOPIC};
+use crate::rpc_proxy::LogsLimit;
+use crate::test_suite::TestContext;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Dense chain every limit is tested on: 10 Transfers in every other block
+const FIXTURE: &str = "backfill-20k";
+
+pub struct ProviderLimitTests;
+
+impl TestModule for ProviderLimitTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        let limited = |name, description, function| {
+            TestDefinition::new(name, description, function)
+                .with_timeout(600)
+                .with_tags(&[TestTag::Historic, TestTag::Slow])
+                .deploys(&["SimpleERC20"])
+                .with_anvil(AnvilOptions::new().with_fixture(FIXTURE))
+        };
+        vec![
+            limited(
+                "test_logs_limit_block_span",
+                "Every Transfer of a dense fixture is indexed when eth_getLogs is limited to 500-block ranges",
+                block_span_test,
+            ),
+            limited(
+                "test_logs_limit_result_count",
+                "Every Transfer of a dense fixture is indexed when eth_getLogs is capped at 2,000 results with no hint",
+                result_count_test,
+            ),
+            limited(
+                "test_logs_limit_suggested_range",
+                "Every Transfer of a dense fixture is indexed through 'more than 10000 results' errors suggesting a range",
+                suggested_range_test,
+            ),
+        ]
+    }
+}
+
+fn block_span_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(index_through_limit(context, LogsLimit::BlockSpan(500)))
+}
+
+fn result_count_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(index_through_limit(context, LogsLimit::Results(2_000)))
+}
+
+fn suggested_range_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(index_through_limit(context, LogsLimit::ResultsWithSuggestedRange(10_000)))
+}
+
+/// Backfill the fixture through a proxy enforcing `limit`; the limit must
+/// reject some calls and every Transfer must still be indexed exactly once
+async fn index_through_limit(context: &mut TestContext, limit: LogsLimit) -> Result<()> {
+    info!("Running Provider Limit Test: {:?}", limit);
+
+    let manifest = context.anvil.fixture.clone()
+        .ok_or_else(|| anyhow::anyhow!("Anvil was not started from fixture {}", FIXTURE))?;
+    let proxy = context.start_rpc_proxy("anvil").await?;
+    proxy.limit_get_logs(Some(limit));
+
+    let mut config = context.create_contract_config(&manifest.contract_address);
+    config.networks[0].rpc = proxy.url.clone();
+    context.start_rindexer(config).await?;
+    context.wait_for_sync_completion(480).await?;
+    let canonical = context.anvil.get_logs(&manifest.contract_address, TRANSFER_TOPIC).await?;
+    context.stop_and_compare(&canonical).await?;
+
+    let stats = proxy.stats();
+    if stats.limited == 0 {
+        return Err(anyhow::anyhow!("{:?} never rejected an eth_getLogs call", limit));
+    }
+
+    info!(
+        "✓ Provider Limit Test PASSED ({:?}): {} Transfers indexed, {} of {} eth_getLogs calls rejected",
+        limit, canonical.len(), stats.limited, proxy.count("eth_getLogs")
+    );
+    Ok(())
+}
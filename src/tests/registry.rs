@@ -1,10 +1,115 @@
 use anyhow::Result;
+use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
+use std::str::FromStr;
 
+use crate::anvil_setup::AnvilOptions;
+use crate::contracts::has_artifact;
 use crate::test_suite::TestContext;
+use crate::tests::capabilities::Capability;
 
-pub type TestFunction = fn(&mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>>;
+pub type TestFunction = fn(&mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>>;
+
+/// Category labels used to select subsets of the registry from the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TestTag {
+    Historic,
+    Live,
+    Postgres,
+    Graphql,
+    Network,
+    Slow,
+}
+
+impl TestTag {
+    pub const ALL: &'static [TestTag] = &[
+        TestTag::Historic,
+        TestTag::Live,
+        TestTag::Postgres,
+        TestTag::Graphql,
+        TestTag::Network,
+        TestTag::Slow,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TestTag::Historic => "historic",
+            TestTag::Live => "live",
+            TestTag::Postgres => "postgres",
+            TestTag::Graphql => "graphql",
+            TestTag::Network => "network",
+            TestTag::Slow => "slow",
+        }
+    }
+}
+
+impl fmt::Display for TestTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TestTag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        TestTag::ALL
+            .iter()
+            .copied()
+            .find(|tag| tag.as_str() == s)
+            .ok_or_else(|| {
+                let known: Vec<&str> = TestTag::ALL.iter().map(|t| t.as_str()).collect();
+                format!("Unknown tag '{}', expected one of: {}", s, known.join(", "))
+            })
+    }
+}
+
+/// Tag expression: comma-separated alternatives, each a `+`-joined set of tags
+/// that must all be present (e.g. `historic,postgres+live`).
+#[derive(Debug, Clone)]
+pub struct TagExpression {
+    alternatives: Vec<Vec<TestTag>>,
+}
+
+impl TagExpression {
+    pub fn matches(&self, tags: &[TestTag]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|all_of| all_of.iter().all(|tag| tags.contains(tag)))
+    }
+}
+
+impl fmt::Display for TagExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let alternatives: Vec<String> = self
+            .alternatives
+            .iter()
+            .map(|all_of| all_of.iter().map(|t| t.as_str()).collect::<Vec<_>>().join("+"))
+            .collect();
+        f.write_str(&alternatives.join(","))
+    }
+}
+
+impl FromStr for TagExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut alternatives = Vec::new();
+        for alternative in s.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            let all_of = alternative
+                .split('+')
+                .map(TestTag::from_str)
+                .collect::<Result<Vec<_>, _>>()?;
+            alternatives.push(all_of);
+        }
+        if alternatives.is_empty() {
+            return Err("Tag expression is empty".to_string());
+        }
+        Ok(Self { alternatives })
+    }
+}
 
 #[derive(Clone)]
 pub struct TestDefinition {
@@ -13,6 +118,9 @@ pub struct TestDefinition {
     pub function: TestFunction,
     pub timeout_seconds: u64,
     pub is_live_test: bool, // true for live indexing tests that need a feeder
+    pub tags: Vec<TestTag>,
+    pub requires: Vec<Capability>, // probed once per run; missing ones skip the test
+    pub anvil: AnvilOptions, // the port is always replaced by the test's own Anvil port
 }
 
 impl TestDefinition {
@@ -23,17 +131,105 @@ impl TestDefinition {
             function,
             timeout_seconds: 180, // Default 3 minutes
             is_live_test: false,
+            tags: Vec::new(),
+            // Every test context starts Anvil and most tests run the rindexer binary
+            requires: vec![Capability::Anvil, Capability::Rindexer { min_version: None }],
+            anvil: AnvilOptions::default(),
+        }
+    }
+
+    /// Start the test's Anvil with `options` instead of the defaults
+    pub fn with_anvil(mut self, options: AnvilOptions) -> Self {
+        self.anvil = options;
+        self
+    }
+
+    pub fn requires(mut self, capabilities: &[Capability]) -> Self {
+        for capability in capabilities {
+            if !self.requires.contains(capability) {
+                self.requires.push(capability.clone());
+            }
+        }
+        self
+    }
+
+    /// Deploys `contracts`; forge is only needed for those without a checked-in artifact
+    pub fn deploys(self, contracts: &[&str]) -> Self {
+        if contracts.iter().all(|name| has_artifact(name)) {
+            self
+        } else {
+            self.requires(&[Capability::Forge])
         }
     }
 
+    pub fn with_tags(mut self, tags: &[TestTag]) -> Self {
+        for tag in tags {
+            if !self.tags.contains(tag) {
+                self.tags.push(*tag);
+            }
+        }
+        self
+    }
+
+    pub fn has_tag(&self, tag: TestTag) -> bool {
+        self.tags.contains(&tag)
+    }
+
     pub fn with_timeout(mut self, timeout_seconds: u64) -> Self {
         self.timeout_seconds = timeout_seconds;
         self
     }
 
+    #[allow(clippy::wrong_self_convention)]
     pub fn as_live_test(mut self) -> Self {
         self.is_live_test = true;
-        self
+        // The runner deploys the feeder's contract
+        self.with_tags(&[TestTag::Live]).deploys(&["SimpleERC20"])
+    }
+}
+
+impl fmt::Display for TestSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(names) = &self.names {
+            parts.push(format!("tests={}", names.join(",")));
+        }
+        if let Some(tags) = &self.tags {
+            parts.push(format!("tags={}", tags));
+        }
+        if !self.exclude_tags.is_empty() {
+            let excluded: Vec<&str> = self.exclude_tags.iter().map(|t| t.as_str()).collect();
+            parts.push(format!("exclude-tags={}", excluded.join(",")));
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
+/// Which registry tests to run: by name and/or by tag
+#[derive(Debug, Clone, Default)]
+pub struct TestSelection {
+    pub names: Option<Vec<String>>,
+    pub tags: Option<TagExpression>,
+    pub exclude_tags: Vec<TestTag>,
+}
+
+impl TestSelection {
+    pub fn is_empty(&self) -> bool {
+        self.names.is_none() && self.tags.is_none() && self.exclude_tags.is_empty()
+    }
+
+    pub fn matches(&self, test: &TestDefinition) -> bool {
+        if let Some(names) = &self.names {
+            if !names.iter().any(|n| n == test.name) {
+                return false;
+            }
+        }
+        if let Some(tags) = &self.tags {
+            if !tags.matches(&test.tags) {
+                return false;
+            }
+        }
+        !self.exclude_tags.iter().any(|tag| test.has_tag(*tag))
     }
 }
 
@@ -78,19 +274,78 @@ impl TestRegistry {
         // Multi-network tests
         tests.extend(crate::tests::multi_network::MultiNetworkTests::get_tests());
 
+        // Signal handling / graceful shutdown
+        tests.extend(crate::tests::shutdown_signals::ShutdownSignalTests::get_tests());
+
+        // CLI subcommands
+        tests.extend(crate::tests::cli_commands::CliCommandTests::get_tests());
+
+        // Anvil launch options (chain id, mining regime)
+        tests.extend(crate::tests::anvil_options::AnvilOptionsTests::get_tests());
+
+        // Chain reorganizations
+        tests.extend(crate::tests::reorg::ReorgTests::get_tests());
+
+        // Contract deployment from checked-in artifacts
+        tests.extend(crate::tests::deployments::DeploymentTests::get_tests());
+
+        // Cheatcodes and named test accounts
+        tests.extend(crate::tests::cheatcodes::CheatcodeTests::get_tests());
+
+        // Large historic backfills from pre-built fixture chains
+        tests.extend(crate::tests::backfill::BackfillTests::get_tests());
+
+        // Historic indexing across millions of empty blocks
+        tests.extend(crate::tests::sparse_history::SparseHistoryTests::get_tests());
+
+        // Local forks of a local Anvil
+        tests.extend(crate::tests::forked_anvil::ForkedAnvilTests::get_tests());
+
+        // RPC fault injection between rindexer and Anvil
+        tests.extend(crate::tests::rpc_faults::RpcFaultTests::get_tests());
+
+        // Hosted-provider eth_getLogs limits
+        tests.extend(crate::tests::provider_limits::ProviderLimitTests::get_tests());
+
+        // Recording RPC traffic and replaying it offline
+        tests.extend(crate::tests::rpc_cassettes::RpcCassetteTests::get_tests());
+
+        // Indexing over WebSocket RPC endpoints
+        tests.extend(crate::tests::ws_transport::WsTransportTests::get_tests());
+
         tests
     }
 
-    pub fn get_tests_by_filter(filter: &[String]) -> Vec<TestDefinition> {
-        let all_tests = Self::get_all_tests();
-        
-        if filter.is_empty() {
-            return all_tests;
-        }
-
-        all_tests
+    pub fn get_tests_by_selection(selection: &TestSelection) -> Vec<TestDefinition> {
+        Self::get_all_tests()
             .into_iter()
-            .filter(|test| filter.contains(&test.name.to_string()))
+            .filter(|test| selection.matches(test))
             .collect()
     }
+
+    /// Print every selected test with its tags, timeout and description
+    pub fn print_test_list(selection: &TestSelection) {
+        let tests = Self::get_tests_by_selection(selection);
+        let name_width = tests.iter().map(|t| t.name.len()).max().unwrap_or(0);
+        let tags: Vec<String> = tests
+            .iter()
+            .map(|t| {
+                let names: Vec<&str> = t.tags.iter().map(|tag| tag.as_str()).collect();
+                format!("[{}]", names.join(","))
+            })
+            .collect();
+        let tag_width = tags.iter().map(|t| t.len()).max().unwrap_or(0);
+
+        for (test, tags) in tests.iter().zip(&tags) {
+            println!(
+                "{:<name_width$}  {:<tag_width$}  {:>4}s  {}",
+                test.name,
+                tags,
+                test.timeout_seconds,
+                test.description,
+            );
+        }
+        println!();
+        println!("{} tests", tests.len());
+    }
 }
@@ -0,0 +1,180 @@
+use anyhow::Result;
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::anvil_setup::{ChainLog, ReorgTransaction, TRANSFER_TOPIC};
+use crate::rindexer_client::postgres_env;
+use crate::test_suite::{compare_with_canonical, TestContext};
+use crate::tests::capabilities::Capability;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Blocks replaced by `anvil_reorg` in the replacement tests
+const REORG_DEPTH: u64 = 3;
+
+pub struct ReorgTests;
+
+impl TestModule for ReorgTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_reorg_csv_canonical",
+                "A depth-3 reorg during live indexing leaves the CSV matching the canonical chain",
+                reorg_csv_test,
+            ).with_timeout(120)
+             .as_live_test(),
+            TestDefinition::new(
+                "test_reorg_postgres_canonical",
+                "A depth-3 reorg during live indexing leaves Postgres matching the canonical chain",
+                reorg_postgres_test,
+            ).with_timeout(180)
+             .as_live_test()
+             .with_tags(&[TestTag::Postgres, TestTag::Slow])
+             .requires(&[Capability::Docker]),
+            TestDefinition::new(
+                "test_revert_reorg_csv_canonical",
+                "Reverting to a snapshot mid-indexing and re-mining leaves the CSV matching the canonical chain",
+                revert_reorg_csv_test,
+            ).with_timeout(120)
+             .as_live_test(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Storage {
+    Csv,
+    Postgres,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ReorgKind {
+    /// `anvil_reorg`: replace the last blocks with different transactions
+    Replace,
+    /// `evm_revert` to a snapshot; the live feeder mines the replacement blocks
+    Revert,
+}
+
+fn reorg_csv_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(reorg_during_live_indexing(context, Storage::Csv, ReorgKind::Replace))
+}
+
+fn reorg_postgres_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(reorg_during_live_indexing(context, Storage::Postgres, ReorgKind::Replace))
+}
+
+fn revert_reorg_csv_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(reorg_during_live_indexing(context, Storage::Csv, ReorgKind::Revert))
+}
+
+async fn reorg_during_live_indexing(context: &mut TestContext, storage: Storage, kind: ReorgKind) -> Result<()> {
+    info!("Running {:?} Reorg Test with {:?} storage", kind, storage);
+
+    let contract_address = context.test_contract_address.clone()
+        .ok_or_else(|| anyhow::anyhow!("No contract address available for live test"))?;
+
+    let pg_port = match storage {
+        Storage::Csv => {
+            let config = context.create_contract_config(&contract_address);
+            context.start_rindexer(config).await?;
+            None
+        }
+        Storage::Postgres => {
+            let pg_port = context.start_postgres().await?;
+            let mut config = context.create_contract_config(&contract_address);
+            config.storage.postgres.enabled = true;
+            config.storage.csv.enabled = false;
+            context.start_rindexer_with_env(config, postgres_env(pg_port)).await?;
+            Some(pg_port)
+        }
+    };
+    context.wait_for_sync_completion(60).await?;
+
+    let snapshot = context.anvil.snapshot().await?;
+    let fork_point = context.anvil.get_block_number().await?;
+
+    // Let rindexer index some live blocks that are about to be replaced
+    tokio::time::sleep(Duration::from_secs(6)).await;
+
+    let head = context.anvil.get_block_number().await?;
+    let first_replaced = match kind {
+        ReorgKind::Replace => head.checked_sub(REORG_DEPTH).map(|below| below + 1)
+            .ok_or_else(|| anyhow::anyhow!("Chain head {} is too shallow for a {}-block reorg", head, REORG_DEPTH))?,
+        ReorgKind::Revert => fork_point + 1,
+    };
+    let replaced_hash = context.anvil.get_block_hash(first_replaced).await?;
+
+    match kind {
+        ReorgKind::Replace => {
+            let deployer = context.anvil.deployer().address.to_string();
+            let replacements: Vec<ReorgTransaction> = (0..REORG_DEPTH)
+                .map(|i| ReorgTransaction::erc20_transfer(
+                    &deployer,
+                    &contract_address,
+                    &format!("0x5e{:038x}", i),
+                    7_000 + i,
+                    i,
+                ))
+                .collect();
+            context.anvil.reorg(REORG_DEPTH, &replacements).await?;
+        }
+        ReorgKind::Revert => {
+            context.anvil.revert(&snapshot).await?;
+            info!("Reverted chain from block {} to {}", head, fork_point);
+        }
+    }
+
+    // Keep feeding so the new branch grows past the old head
+    tokio::time::sleep(Duration::from_secs(8)).await;
+
+    let new_hash = context.anvil.get_block_hash(first_replaced).await?;
+    if new_hash == replaced_hash {
+        return Err(anyhow::anyhow!("Block {} still has hash {} after the reorg", first_replaced, new_hash));
+    }
+    info!("Block {} replaced: {} -> {}", first_replaced, replaced_hash, new_hash);
+
+    if let Some(feeder) = context.live_feeder.take() {
+        feeder.stop();
+    }
+    tokio::time::sleep(Duration::from_secs(5)).await;
+
+    context.stop_rindexer().await?;
+
+    let canonical = context.anvil.get_logs(&contract_address, TRANSFER_TOPIC).await?;
+    let indexed = match pg_port {
+        None => context.read_indexed_transfers()?,
+        Some(pg_port) => read_postgres_logs(pg_port).await?,
+    };
+    compare_with_canonical(&indexed, &canonical)?;
+
+    info!(
+        "✓ {:?} Reorg Test PASSED ({:?}): {} indexed Transfer logs match the canonical chain",
+        kind, storage, indexed.len()
+    );
+    Ok(())
+}
+
+async fn read_postgres_logs(pg_port: u16) -> Result<Vec<ChainLog>> {
+    let (client, connection) = tokio_postgres::connect(
+        &crate::test_suite::postgres_connection_string(pg_port),
+        tokio_postgres::NoTls,
+    ).await?;
+    tokio::spawn(async move { let _ = connection.await; });
+
+    // Table naming follows rindexer logs: schema `contract_test_simple_erc_20`, table `transfer`
+    let rows = client.query(
+        "SELECT block_number::TEXT, block_hash::TEXT, tx_hash::TEXT, log_index::TEXT \
+         FROM contract_test_simple_erc_20.transfer",
+        &[],
+    ).await?;
+    rows.iter().map(|row| {
+        let text = |i: usize| -> String { row.get::<_, String>(i).trim().to_lowercase() };
+        Ok(ChainLog {
+            block_number: text(0).parse()?,
+            block_hash: text(1),
+            tx_hash: text(2),
+            log_index: text(3).parse()?,
+        })
+    }).collect()
+}
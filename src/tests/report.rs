@@ -0,0 +1,214 @@
+use anyhow::{Result, Context};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::info;
+
+use crate::tests::test_suite::{TestInfo, TestResult, TestSuite};
+
+/// A destination for machine-readable test results
+pub trait ReportSink {
+    fn write(&self, suite: &TestSuite) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+/// A `--report FORMAT=PATH` request from the command line
+#[derive(Debug, Clone)]
+pub struct ReportSpec {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl FromStr for ReportSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid report '{}', expected FORMAT=PATH (e.g. junit=report.xml)", s))?;
+        let format = match format.trim().to_lowercase().as_str() {
+            "junit" | "xml" => ReportFormat::Junit,
+            "json" => ReportFormat::Json,
+            other => return Err(format!("Unknown report format '{}', expected junit or json", other)),
+        };
+        let path = path.trim();
+        if path.is_empty() {
+            return Err(format!("Report '{}' is missing an output path", s));
+        }
+        Ok(Self { format, path: PathBuf::from(path) })
+    }
+}
+
+impl ReportSpec {
+    pub fn into_sink(self) -> Box<dyn ReportSink> {
+        match self.format {
+            ReportFormat::Junit => Box::new(JunitReportSink { path: self.path }),
+            ReportFormat::Json => Box::new(JsonReportSink { path: self.path }),
+        }
+    }
+}
+
+/// Write every requested report for a finished suite
+pub fn write_reports(suite: &TestSuite, specs: &[ReportSpec]) -> Result<()> {
+    for spec in specs {
+        spec.clone().into_sink().write(suite)?;
+        info!("Wrote {:?} report to {}", spec.format, spec.path.display());
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, content: String) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create report directory {}", parent.display()))?;
+        }
+    }
+    std::fs::write(path, content)
+        .with_context(|| format!("Failed to write report {}", path.display()))
+}
+
+/// Human-readable explanation attached to a non-passing result
+fn result_message(test: &TestInfo) -> Option<String> {
+    match &test.result {
+        TestResult::Passed => None,
+        TestResult::Failed(msg) => Some(msg.clone()),
+        TestResult::Timeout => Some(format!("Test timed out after {} seconds", test.duration.as_secs())),
+        TestResult::Skipped(reason) => Some(reason.clone()),
+    }
+}
+
+/// JUnit XML report, as understood by most CI systems
+pub struct JunitReportSink {
+    pub path: PathBuf,
+}
+
+impl ReportSink for JunitReportSink {
+    fn write(&self, suite: &TestSuite) -> Result<()> {
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(&suite.name),
+            suite.total_count(),
+            suite.failed_count(),
+            suite.timeout_count(),
+            suite.skipped_count(),
+            suite.duration.as_secs_f64(),
+        ));
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(&suite.name),
+            suite.total_count(),
+            suite.failed_count(),
+            suite.timeout_count(),
+            suite.skipped_count(),
+            suite.duration.as_secs_f64(),
+        ));
+
+        for test in &suite.tests {
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"rindexer-e2e\" time=\"{:.3}\"",
+                xml_escape(&test.name),
+                test.duration.as_secs_f64(),
+            );
+            let message = result_message(test).map(|m| xml_escape(&m)).unwrap_or_default();
+            let outcome = match &test.result {
+                TestResult::Passed => String::new(),
+                TestResult::Failed(_) => format!(
+                    "      <failure message=\"{}\">{}</failure>\n",
+                    message, message
+                ),
+                TestResult::Timeout => format!(
+                    "      <error type=\"timeout\" message=\"{}\">{}</error>\n",
+                    message, message
+                ),
+                TestResult::Skipped(_) => format!("      <skipped message=\"{}\" />\n", message),
+            };
+            let mut problems = Vec::new();
+            if !test.teardown_errors.is_empty() {
+                problems.push(format!("Teardown errors:\n{}", test.teardown_errors.join("\n")));
+            }
+            if !test.leaks.is_empty() {
+                problems.push(format!("Leaked resources:\n{}", test.leaks.join("\n")));
+            }
+            let teardown = if problems.is_empty() {
+                String::new()
+            } else {
+                format!("      <system-err>{}</system-err>\n", xml_escape(&problems.join("\n")))
+            };
+
+            if outcome.is_empty() && teardown.is_empty() {
+                xml.push_str(&format!("{} />\n", open));
+            } else {
+                xml.push_str(&format!("{}>\n{}{}    </testcase>\n", open, outcome, teardown));
+            }
+        }
+
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        write_file(&self.path, xml)
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters other than whitespace are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Machine-readable JSON report
+pub struct JsonReportSink {
+    pub path: PathBuf,
+}
+
+impl ReportSink for JsonReportSink {
+    fn write(&self, suite: &TestSuite) -> Result<()> {
+        let tests: Vec<serde_json::Value> = suite.tests.iter().map(|test| {
+            let (status, message, skip_reason) = match &test.result {
+                TestResult::Passed => ("passed", None, None),
+                TestResult::Failed(_) => ("failed", result_message(test), None),
+                TestResult::Timeout => ("timeout", result_message(test), None),
+                TestResult::Skipped(reason) => ("skipped", None, Some(reason.clone())),
+            };
+            serde_json::json!({
+                "name": test.name,
+                "result": status,
+                "message": message,
+                "skip_reason": skip_reason,
+                "duration_secs": test.duration.as_secs_f64(),
+                "teardown_errors": test.teardown_errors,
+                "leaks": test.leaks,
+            })
+        }).collect();
+
+        let report = serde_json::json!({
+            "name": suite.name,
+            "duration_secs": suite.duration.as_secs_f64(),
+            "summary": {
+                "total": suite.total_count(),
+                "passed": suite.passed_count(),
+                "failed": suite.failed_count(),
+                "timeout": suite.timeout_count(),
+                "skipped": suite.skipped_count(),
+            },
+            "tests": tests,
+        });
+
+        write_file(&self.path, serde_json::to_string_pretty(&report)?)
+    }
+}
@@ -3,8 +3,9 @@ use tracing::info;
 use std::pin::Pin;
 use std::future::Future;
 
+use crate::rpc_proxy::{get_logs_coverage, RpcCall};
 use crate::test_suite::TestContext;
-use crate::tests::registry::{TestDefinition, TestModule};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
 
 pub struct RestartCheckpointTests;
 
@@ -13,24 +14,30 @@ impl TestModule for RestartCheckpointTests {
         vec![
             TestDefinition::new(
                 "test_restart_checkpoint_no_duplicates",
-                "Restart indexer and ensure no duplicate events are written",
+                "Restart indexer and ensure no duplicate events are written and no eth_getLogs goes below the checkpoint",
                 restart_checkpoint_no_duplicates_test,
-            ).with_timeout(180),
+            ).with_timeout(180)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"]),
         ]
     }
 }
 
-fn restart_checkpoint_no_duplicates_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+fn restart_checkpoint_no_duplicates_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
     Box::pin(async move {
         info!("Running Restart/Checkpoint Test: No Duplicates");
 
-        // Deploy contract and start indexer for historic indexing
+        // Deploy contract and start indexer for historic indexing, recording its RPC calls
         let contract_address = context.deploy_test_contract().await?;
-        let config = context.create_contract_config(&contract_address);
+        let proxy = context.start_rpc_proxy("anvil").await?;
+        let mut config = context.create_contract_config(&contract_address);
+        config.networks[0].rpc = proxy.url.clone();
         context.start_rindexer(config).await?;
 
         // Wait for initial historic sync to complete
         context.wait_for_sync_completion(20).await?;
+        let checkpoint = synced_to(&proxy.take_calls())?;
+        info!("Initial sync fetched logs up to block {}", checkpoint);
 
         // Count events after first run
         let first_count = context.get_event_count()?;
@@ -42,12 +49,26 @@ fn restart_checkpoint_no_duplicates_test(context: &mut TestContext) -> Pin<Box<d
         }
 
         // Restart the indexer with the same project path and config
-        let config = context.create_contract_config(&contract_address);
+        proxy.take_calls();
+        let mut config = context.create_contract_config(&contract_address);
+        config.networks[0].rpc = proxy.url.clone();
         context.start_rindexer(config).await?;
 
         // Wait for sync (should be quick if checkpointing works)
         context.wait_for_sync_completion(20).await?;
 
+        // Everything up to the checkpoint is indexed already and must not be fetched again
+        let refetched: Vec<RpcCall> = proxy.calls().into_iter()
+            .filter(|call| call.method == "eth_getLogs" && call.from_block.is_some_and(|from| from < checkpoint))
+            .collect();
+        if !refetched.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Restart issued {} eth_getLogs below checkpoint block {}: {:?}",
+                refetched.len(), checkpoint, refetched.iter().map(|c| &c.params).collect::<Vec<_>>()
+            ));
+        }
+        info!("Calls after restart: {:?}", proxy.call_counts());
+
         // Count events again and ensure no duplicates were written
         let second_count = context.get_event_count()?;
         info!("Event count after restart: {}", second_count);
@@ -65,3 +86,10 @@ fn restart_checkpoint_no_duplicates_test(context: &mut TestContext) -> Pin<Box<d
 }
 
 
+
+/// Last block the initial sync fetched logs for
+fn synced_to(calls: &[RpcCall]) -> Result<u64> {
+    get_logs_coverage(calls).last()
+        .map(|range| *range.end())
+        .ok_or_else(|| anyhow::anyhow!("Initial sync issued no eth_getLogs with a numeric block range"))
+}
@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+
+use crate::anvil_setup::{ChainLog, AnvilOptions, Mining, TRANSFER_TOPIC};
+use crate::rpc_proxy::RpcProxy;
+use crate::test_suite::{RindexerConfig, TestContext};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Transfers on the chain before the recording run, besides the constructor mint
+const HISTORIC_TRANSFERS: u64 = 10;
+
+pub struct RpcCassetteTests;
+
+impl TestModule for RpcCassetteTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_cassette_record_replay",
+                "A historic sync recorded from Anvil replays from the cassette alone, with Anvil stopped",
+                record_replay_test,
+            ).with_timeout(180)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_mining(Mining::Auto)),
+        ]
+    }
+}
+
+fn record_replay_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running RPC Cassette Record/Replay Test");
+
+        let contract_address = context.deploy_test_contract().await?;
+        context.send_transfers(&contract_address, HISTORIC_TRANSFERS).await?;
+        let canonical = context.anvil.get_logs(&contract_address, TRANSFER_TOPIC).await?;
+        // Next to the project rather than in data/cassettes, so it goes away with the test
+        let cassette = context.project_path.with_file_name("anvil.cassette.json");
+
+        let recorder = RpcProxy::record(&context.anvil.rpc_url, &cassette, context.logs.create("rpc-record")).await?;
+        context.rpc_proxies.push(recorder.clone());
+        let mut config = context.create_contract_config(&contract_address);
+        config.networks[0].rpc = recorder.url.clone();
+        sync_and_compare(context, config, &canonical).await
+            .context("Recording run")?;
+        recorder.save_cassette()?;
+        let recorded = recorder.stats().requests;
+
+        // Nothing is left to answer but the cassette
+        context.anvil.stop().await?;
+        std::fs::remove_dir_all(context.get_csv_output_path())
+            .context("Failed to clear the recording run's CSV output")?;
+
+        let replayer = RpcProxy::replay(&cassette, context.logs.create("rpc-replay")).await?;
+        context.rpc_proxies.push(replayer.clone());
+        let mut config = context.create_contract_config(&contract_address);
+        config.networks[0].rpc = replayer.url.clone();
+        sync_and_compare(context, config, &canonical).await
+            .context("Replay run")?;
+
+        let stats = replayer.stats();
+        if stats.unrecorded > 0 {
+            return Err(anyhow::anyhow!(
+                "{} of {} replayed requests were not on the cassette", stats.unrecorded, stats.requests
+            ));
+        }
+        info!(
+            "✓ RPC Cassette Record/Replay Test PASSED: {} Transfers from {} recorded requests, all {} replayed requests answered",
+            canonical.len(), recorded, stats.requests
+        );
+        Ok(())
+    })
+}
+
+/// Run a historic sync of `config` and check what rindexer indexed against `canonical`
+async fn sync_and_compare(context: &mut TestContext, config: RindexerConfig, canonical: &[ChainLog]) -> Result<()> {
+    context.start_rindexer(config).await?;
+    context.wait_for_sync_completion(60).await?;
+    context.stop_and_compare(canonical).await?;
+    Ok(())
+}
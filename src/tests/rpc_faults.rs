@@ -0,0 +1,185 @@
+use anyhow::Result;
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::anvil_setup::{AnvilOptions, Mining, TRANSFER_TOPIC};
+use crate::rpc_proxy::{Fault, FaultRule, RpcProxy};
+use crate::test_suite::TestContext;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Transfers on the chain before rindexer starts, besides the constructor mint
+const HISTORIC_TRANSFERS: u64 = 10;
+
+/// How long every eth_getLogs fails before the outage test clears its faults
+const OUTAGE: Duration = Duration::from_secs(10);
+
+/// How long the live partition test keeps rindexer cut off from Anvil
+const PARTITION: Duration = Duration::from_secs(10);
+
+pub struct RpcFaultTests;
+
+impl TestModule for RpcFaultTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        let historic = |name, description, function| {
+            TestDefinition::new(name, description, function)
+                .with_timeout(180)
+                .with_tags(&[TestTag::Historic])
+                .deploys(&["SimpleERC20"])
+                .with_anvil(AnvilOptions::new().with_mining(Mining::Auto))
+        };
+        vec![
+            historic(
+                "test_rpc_http_500_retried",
+                "Historic sync retries eth_getLogs answered with HTTP 500 and indexes every Transfer",
+                http_500_test,
+            ),
+            historic(
+                "test_rpc_rate_limited_429",
+                "Historic sync backs off from HTTP 429 rate limiting and indexes every Transfer",
+                rate_limited_test,
+            ),
+            historic(
+                "test_rpc_jsonrpc_error_retried",
+                "Historic sync retries eth_getLogs answered with a JSON-RPC internal error",
+                jsonrpc_error_test,
+            ),
+            historic(
+                "test_rpc_dropped_connection_retried",
+                "Historic sync recovers from connections closed without a response",
+                dropped_connection_test,
+            ),
+            historic(
+                "test_rpc_truncated_body_retried",
+                "Historic sync recovers from responses cut off halfway through the body",
+                truncated_body_test,
+            ),
+            historic(
+                "test_rpc_outage_recovers",
+                "Historic sync resumes once an eth_getLogs outage of HTTP 503s is cleared",
+                outage_test,
+            ),
+            historic(
+                "test_rpc_latency",
+                "Historic sync completes with every RPC call delayed by 300ms",
+                latency_test,
+            ),
+            TestDefinition::new(
+                "test_rpc_partition_live",
+                "Live indexing resumes after a 10s RPC partition without losing events",
+                partition_live_test,
+            ).with_timeout(180)
+             .as_live_test(),
+        ]
+    }
+}
+
+fn http_500_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(historic_with_faults(context, FaultRule::new(Fault::HttpStatus(500)).for_method("eth_getLogs").times(3), None))
+}
+
+fn rate_limited_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(historic_with_faults(context, FaultRule::new(Fault::HttpStatus(429)).for_method("eth_getLogs").times(3), None))
+}
+
+fn jsonrpc_error_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    let fault = Fault::JsonRpcError { code: -32603, message: "injected internal error".to_string() };
+    Box::pin(historic_with_faults(context, FaultRule::new(fault).for_method("eth_getLogs").times(3), None))
+}
+
+fn dropped_connection_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(historic_with_faults(context, FaultRule::new(Fault::DropConnection).for_method("eth_getLogs").times(2), None))
+}
+
+fn truncated_body_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(historic_with_faults(context, FaultRule::new(Fault::TruncateBody).for_method("eth_getLogs").times(2), None))
+}
+
+fn outage_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(historic_with_faults(context, FaultRule::new(Fault::HttpStatus(503)).for_method("eth_getLogs"), Some(OUTAGE)))
+}
+
+fn latency_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(historic_with_faults(context, FaultRule::new(Fault::Latency(Duration::from_millis(300))), None))
+}
+
+/// Index a chain of finished transfers through a proxy injecting `rule`,
+/// cleared after `outage` when set; the fault must be hit and every Transfer
+/// must still be indexed exactly once
+async fn historic_with_faults(context: &mut TestContext, rule: FaultRule, outage: Option<Duration>) -> Result<()> {
+    info!("Running RPC Fault Test: {:?}", rule.fault);
+
+    let contract_address = context.deploy_test_contract().await?;
+    context.send_transfers(&contract_address, HISTORIC_TRANSFERS).await?;
+
+    let proxy = context.start_rpc_proxy("anvil").await?;
+    proxy.inject(rule.clone());
+    let mut config = context.create_contract_config(&contract_address);
+    config.networks[0].rpc = proxy.url.clone();
+
+    context.start_rindexer(config).await?;
+    if let Some(outage) = outage {
+        tokio::time::sleep(outage).await;
+        proxy.clear_faults();
+    }
+    context.wait_for_sync_completion(120).await?;
+    let faults = require_faults(&proxy)?;
+    // Every faulted call has to be retried for the sync to complete
+    if let (Some(method), Some(_)) = (&rule.method, rule.remaining) {
+        let issued = proxy.count(method);
+        if issued <= faults {
+            return Err(anyhow::anyhow!("Rindexer issued {} {} calls for {} injected faults and never retried", issued, method, faults));
+        }
+    }
+
+    let canonical = context.anvil.get_logs(&contract_address, TRANSFER_TOPIC).await?;
+    context.stop_and_compare(&canonical).await?;
+    info!("✓ RPC Fault Test PASSED: {} injected faults, no events lost", faults);
+    Ok(())
+}
+
+fn partition_live_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running RPC Partition Live Test");
+
+        let contract_address = context.test_contract_address.clone()
+            .ok_or_else(|| anyhow::anyhow!("No contract address available for live test"))?;
+
+        let proxy = context.start_rpc_proxy("anvil").await?;
+        let mut config = context.create_contract_config(&contract_address);
+        config.networks[0].rpc = proxy.url.clone();
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(60).await?;
+
+        // The feeder keeps sending to Anvil directly while rindexer is cut off
+        proxy.partition();
+        tokio::time::sleep(PARTITION).await;
+        proxy.heal();
+        let dropped = proxy.stats().partitioned;
+        if dropped == 0 {
+            return Err(anyhow::anyhow!("Rindexer made no RPC calls during the {:?} partition", PARTITION));
+        }
+
+        // Let rindexer catch up on the blocks mined during the partition
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        if let Some(feeder) = context.live_feeder.take() {
+            feeder.stop();
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        let canonical = context.anvil.get_logs(&contract_address, TRANSFER_TOPIC).await?;
+        context.stop_and_compare(&canonical).await?;
+        info!("✓ RPC Partition Live Test PASSED: {} calls dropped during the partition, no events lost", dropped);
+        Ok(())
+    })
+}
+
+/// Faults the proxy injected; none means the test did not exercise anything
+fn require_faults(proxy: &RpcProxy) -> Result<u64> {
+    let stats = proxy.stats();
+    if stats.faults == 0 {
+        return Err(anyhow::anyhow!("No fault was injected in {} proxied requests", stats.requests));
+    }
+    Ok(stats.faults)
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use nix::sys::signal::Signal;
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::anvil_setup::TRANSFER_TOPIC;
+use crate::rindexer_client::postgres_env;
+use crate::test_suite::TestContext;
+use crate::tests::capabilities::Capability;
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// How long rindexer gets to exit on its own after the signal
+const GRACE_PERIOD: Duration = Duration::from_secs(15);
+
+pub struct ShutdownSignalTests;
+
+impl TestModule for ShutdownSignalTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_sigterm_flushes_csv",
+                "SIGTERM during live indexing exits zero with every event flushed to CSV",
+                sigterm_flushes_csv_test,
+            ).with_timeout(120)
+             .as_live_test(),
+            TestDefinition::new(
+                "test_sigint_flushes_csv",
+                "SIGINT during live indexing exits zero with every event flushed to CSV",
+                sigint_flushes_csv_test,
+            ).with_timeout(120)
+             .as_live_test(),
+            TestDefinition::new(
+                "test_sigterm_flushes_postgres",
+                "SIGTERM during live indexing exits zero with every event flushed to Postgres",
+                sigterm_flushes_postgres_test,
+            ).with_timeout(180)
+             .as_live_test()
+             .with_tags(&[TestTag::Postgres, TestTag::Slow])
+             .requires(&[Capability::Docker]),
+        ]
+    }
+}
+
+fn sigterm_flushes_csv_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(csv_flushed_on_signal(context, Signal::SIGTERM))
+}
+
+fn sigint_flushes_csv_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(csv_flushed_on_signal(context, Signal::SIGINT))
+}
+
+async fn csv_flushed_on_signal(context: &mut TestContext, signal: Signal) -> Result<()> {
+    info!("Running {} CSV Flush Test", signal);
+
+    let contract_address = context.test_contract_address.clone()
+        .ok_or_else(|| anyhow::anyhow!("No contract address available for live test"))?;
+
+    let config = context.create_contract_config(&contract_address);
+    context.start_rindexer(config).await?;
+    context.wait_for_sync_completion(30).await?;
+
+    let expected = quiesce_and_count(context, &contract_address).await?;
+    signal_and_expect_clean_exit(context, signal).await?;
+
+    // Read only after the process is gone so the count reflects what it flushed
+    let indexed = context.get_event_count()?;
+    if indexed != expected {
+        return Err(anyhow::anyhow!(
+            "CSV has {} Transfer rows after {}, chain has {}",
+            indexed, signal, expected
+        ));
+    }
+
+    info!("✓ {} CSV Flush Test PASSED: exit 0, {} events on disk", signal, indexed);
+    Ok(())
+}
+
+fn sigterm_flushes_postgres_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running SIGTERM Postgres Flush Test");
+
+        let contract_address = context.test_contract_address.clone()
+            .ok_or_else(|| anyhow::anyhow!("No contract address available for live test"))?;
+
+        let pg_port = context.start_postgres().await?;
+        let mut config = context.create_contract_config(&contract_address);
+        config.storage.postgres.enabled = true;
+        config.storage.csv.enabled = false;
+
+        context.start_rindexer_with_env(config, postgres_env(pg_port)).await?;
+        context.wait_for_sync_completion(60).await?;
+
+        let expected = quiesce_and_count(context, &contract_address).await?;
+        signal_and_expect_clean_exit(context, Signal::SIGTERM).await?;
+
+        let (client, connection) = tokio_postgres::connect(
+            &crate::test_suite::postgres_connection_string(pg_port),
+            tokio_postgres::NoTls,
+        ).await?;
+        tokio::spawn(async move { let _ = connection.await; });
+
+        // Table naming follows rindexer logs: schema `contract_test_simple_erc_20`, table `transfer`
+        let row = client.query_one(
+            "SELECT COUNT(*)::BIGINT FROM contract_test_simple_erc_20.transfer",
+            &[],
+        ).await?;
+        let indexed: i64 = row.get(0);
+        if indexed as usize != expected {
+            return Err(anyhow::anyhow!(
+                "Postgres has {} Transfer rows after SIGTERM, chain has {}",
+                indexed, expected
+            ));
+        }
+
+        info!("✓ SIGTERM Postgres Flush Test PASSED: exit 0, {} rows in Postgres", indexed);
+        Ok(())
+    })
+}
+
+/// Let the feeder produce some live events, stop it, give rindexer time to
+/// fetch the last blocks, then return the number of Transfer events on chain
+async fn quiesce_and_count(context: &mut TestContext, contract_address: &str) -> Result<usize> {
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    if let Some(feeder) = context.live_feeder.take() {
+        feeder.stop();
+    }
+    tokio::time::sleep(Duration::from_secs(5)).await;
+
+    let expected = context.anvil.count_logs(contract_address, TRANSFER_TOPIC).await?;
+    info!("Chain has {} Transfer events before shutdown", expected);
+    if expected < 2 {
+        return Err(anyhow::anyhow!("Expected live Transfer events on chain, found {}", expected));
+    }
+    Ok(expected)
+}
+
+async fn signal_and_expect_clean_exit(context: &mut TestContext, signal: Signal) -> Result<()> {
+    let mut rindexer = context.rindexer.take()
+        .ok_or_else(|| anyhow::anyhow!("Rindexer was not started"))?;
+    let outcome = rindexer.shutdown(signal, GRACE_PERIOD).await?;
+    info!("Rindexer shutdown on {}: {:?}", signal, outcome);
+
+    if outcome.escalated {
+        return Err(anyhow::anyhow!(
+            "Rindexer ignored {} for {:?} and had to be killed",
+            signal, GRACE_PERIOD
+        ));
+    }
+    if !outcome.is_clean() {
+        return Err(anyhow::anyhow!("Rindexer exited non-zero on {}: {:?}", signal, outcome.state));
+    }
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use anyhow::{Context, Result};
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::anvil_setup::{erc20_transfer_request, AnvilOptions, ChainLog, Mining, TRANSFER_TOPIC};
+use crate::test_suite::{RindexerConfig, TestContext};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+
+/// Historic sync over a sparse chain must finish within this, measured from rindexer's start
+const SYNC_BUDGET: Duration = Duration::from_secs(180);
+
+/// Empty blocks mined per `anvil_mine` call while building a gap
+const MINE_CHUNK: u64 = 100_000;
+
+/// Transfers sent back to back (one block each) after the deployment and after every gap
+const TRANSFERS_PER_CLUSTER: u64 = 3;
+
+/// Shape of a sparse chain: a transfer cluster right after the deployment and
+/// after each of `gaps` empty blocks, then `trailing_gap` empty blocks to the head
+struct SparseLayout {
+    gaps: &'static [u64],
+    trailing_gap: u64,
+}
+
+/// Two million empty blocks between four clusters
+const MILLION_BLOCK_GAPS: SparseLayout = SparseLayout {
+    gaps: &[1_000_000, 500_000, 500_000],
+    trailing_gap: 0,
+};
+
+/// The last event is a million blocks behind the head
+const TRAILING_GAP: SparseLayout = SparseLayout {
+    gaps: &[250_000],
+    trailing_gap: 1_000_000,
+};
+
+pub struct SparseHistoryTests;
+
+impl TestModule for SparseHistoryTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_sparse_backfill_million_block_gaps",
+                "Rindexer finds every Transfer across 2,000,000 empty blocks within the sync budget",
+                million_block_gaps_test,
+            ).with_timeout(900)
+             .with_tags(&[TestTag::Historic, TestTag::Slow])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_mining(Mining::Auto)),
+            TestDefinition::new(
+                "test_sparse_backfill_range_inside_gaps",
+                "Start and end blocks in the middle of empty gaps index exactly the Transfers between them",
+                range_inside_gaps_test,
+            ).with_timeout(900)
+             .with_tags(&[TestTag::Historic, TestTag::Slow])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_mining(Mining::Auto)),
+            TestDefinition::new(
+                "test_sparse_backfill_trailing_gap",
+                "Historic sync completes when the head is a million empty blocks past the last Transfer",
+                trailing_gap_test,
+            ).with_timeout(900)
+             .with_tags(&[TestTag::Historic, TestTag::Slow])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_mining(Mining::Auto)),
+        ]
+    }
+}
+
+fn million_block_gaps_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Sparse Backfill Million Block Gaps Test");
+
+        let chain = build_sparse_chain(context, &MILLION_BLOCK_GAPS).await?;
+        let config = context.create_contract_config(&chain.contract_address);
+        let indexed = backfill_within_budget(context, config, &chain.transfers).await?;
+
+        info!(
+            "✓ Sparse Backfill Million Block Gaps Test PASSED: {} events up to block {}",
+            indexed.len(), chain.head_block
+        );
+        Ok(())
+    })
+}
+
+fn range_inside_gaps_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Sparse Backfill Range Inside Gaps Test");
+
+        let chain = build_sparse_chain(context, &MILLION_BLOCK_GAPS).await?;
+        // Halfway into the first gap and halfway into the last one
+        let start = chain.cluster_blocks[0] + MILLION_BLOCK_GAPS.gaps[0] / 2;
+        let last_gap = MILLION_BLOCK_GAPS.gaps[MILLION_BLOCK_GAPS.gaps.len() - 1];
+        let end = chain.cluster_blocks[chain.cluster_blocks.len() - 1] - last_gap / 2;
+        let expected: Vec<ChainLog> = chain.transfers.iter()
+            .filter(|log| (start..=end).contains(&log.block_number))
+            .cloned()
+            .collect();
+        if expected.is_empty() {
+            return Err(anyhow::anyhow!("No Transfers between blocks {} and {}", start, end));
+        }
+
+        let mut config = context.create_contract_config(&chain.contract_address);
+        let detail = &mut config.contracts[0].details[0];
+        detail.start_block = start.to_string();
+        detail.end_block = Some(end.to_string());
+        let indexed = backfill_within_budget(context, config, &expected).await?;
+
+        info!(
+            "✓ Sparse Backfill Range Inside Gaps Test PASSED: {} events in blocks {}..={}",
+            indexed.len(), start, end
+        );
+        Ok(())
+    })
+}
+
+fn trailing_gap_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running Sparse Backfill Trailing Gap Test");
+
+        let chain = build_sparse_chain(context, &TRAILING_GAP).await?;
+        let config = context.create_contract_config(&chain.contract_address);
+        let indexed = backfill_within_budget(context, config, &chain.transfers).await?;
+
+        info!(
+            "✓ Sparse Backfill Trailing Gap Test PASSED: {} events, head {} blocks past the last one",
+            indexed.len(), chain.head_block - chain.cluster_blocks[chain.cluster_blocks.len() - 1]
+        );
+        Ok(())
+    })
+}
+
+/// A chain built from a `SparseLayout`
+struct SparseChain {
+    contract_address: String,
+    /// First block of every transfer cluster
+    cluster_blocks: Vec<u64>,
+    head_block: u64,
+    /// Every Transfer on the chain, including the constructor mint
+    transfers: Vec<ChainLog>,
+}
+
+/// Deploy SimpleERC20 and lay out transfer clusters separated by bulk-mined empty blocks
+async fn build_sparse_chain(context: &TestContext, layout: &SparseLayout) -> Result<SparseChain> {
+    let deployment = context.deploy_contract("SimpleERC20", &[]).await?;
+    let deployer = context.anvil.deployer().address.to_string();
+    let started = Instant::now();
+
+    let mut cluster_blocks = Vec::new();
+    let mut recipient = 0u64;
+    for gap in std::iter::once(0).chain(layout.gaps.iter().copied()) {
+        mine_gap(context, gap).await?;
+        cluster_blocks.push(context.anvil.get_block_number().await? + 1);
+        for _ in 0..TRANSFERS_PER_CLUSTER {
+            recipient += 1;
+            // Automine puts every transfer in a block of its own
+            context.anvil.send_unsigned(erc20_transfer_request(
+                &deployer,
+                &deployment.address,
+                &format!("0x5a{:038x}", recipient),
+                recipient,
+            )).await?;
+        }
+    }
+    mine_gap(context, layout.trailing_gap).await?;
+
+    let head_block = context.anvil.get_block_number().await?;
+    let transfers = context.anvil.get_logs(&deployment.address, TRANSFER_TOPIC).await?;
+    let expected = 1 + TRANSFERS_PER_CLUSTER * cluster_blocks.len() as u64;
+    if transfers.len() as u64 != expected {
+        return Err(anyhow::anyhow!(
+            "Sparse chain has {} Transfer logs, expected {}", transfers.len(), expected
+        ));
+    }
+    info!(
+        "Built sparse chain in {:?}: {} Transfers in clusters at blocks {:?}, head {}",
+        started.elapsed(), transfers.len(), cluster_blocks, head_block
+    );
+    Ok(SparseChain { contract_address: deployment.address, cluster_blocks, head_block, transfers })
+}
+
+/// Mine `blocks` empty blocks in `MINE_CHUNK`-sized `anvil_mine` calls
+async fn mine_gap(context: &TestContext, blocks: u64) -> Result<()> {
+    let mut remaining = blocks;
+    while remaining > 0 {
+        let chunk = remaining.min(MINE_CHUNK);
+        context.anvil.mine_n(chunk).await
+            .with_context(|| format!("Failed to mine {} empty blocks", chunk))?;
+        remaining -= chunk;
+    }
+    Ok(())
+}
+
+/// Index `config`, failing if historic sync takes longer than `SYNC_BUDGET`,
+/// and check every Transfer written to the CSV against `expected`
+async fn backfill_within_budget(context: &mut TestContext, config: RindexerConfig, expected: &[ChainLog]) -> Result<Vec<ChainLog>> {
+    let started = Instant::now();
+    context.start_rindexer(config).await?;
+    context.wait_for_sync_completion(SYNC_BUDGET.as_secs()).await
+        .with_context(|| format!("Historic sync did not finish within the {:?} budget", SYNC_BUDGET))?;
+    let elapsed = started.elapsed();
+    info!("Historic sync finished in {:.1}s (budget {:?})", elapsed.as_secs_f64(), SYNC_BUDGET);
+
+    context.stop_and_compare(expected).await
+}
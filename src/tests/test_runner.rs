@@ -1,19 +1,37 @@
 use anyhow::Result;
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use tokio::time::timeout;
-use tracing::info;
+use tracing::{info, error};
 
 use crate::test_suite::TestContext;
 use crate::tests::test_suite::TestSuite;
-use crate::tests::registry::{TestDefinition, TestRegistry};
+use crate::tests::registry::{TestDefinition, TestRegistry, TestSelection};
 use crate::tests::test_suite::{TestInfo, TestResult};
 use thiserror::Error;
 use crate::live_feeder::LiveFeeder;
+use crate::ports::{port_accepting, TestPorts};
+use crate::docker::{container_exists, stop_postgres_container};
+use crate::process_group::reap_leaked_groups;
+use crate::tests::capabilities::CapabilityReport;
+use crate::artifacts::collect_failure_artifacts;
+use crate::process_log::ProcessLogs;
 
 #[derive(Debug, Error)]
 #[error("{0}")]
 pub struct SkipTest(pub String);
 
+const TEARDOWN_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long killed processes get to disappear before they count as leaked
+const LEAK_GRACE: Duration = Duration::from_secs(2);
+
+#[derive(Clone)]
 pub struct TestRunner {
     config: TestRunnerConfig,
 }
@@ -23,6 +41,16 @@ pub struct TestRunnerConfig {
     pub rindexer_binary: String,
     pub anvil_port: u16,
     pub health_port: u16,
+    pub graphql_port: u16,
+    /// Number of tests to run concurrently. With more than one job every test
+    /// gets its own dynamically allocated ports instead of the fixed ones above.
+    pub jobs: usize,
+    /// Failing tests write a debug bundle to `<artifacts_dir>/<test name>/`
+    pub artifacts_dir: PathBuf,
+    /// Preserve each test's temporary project directory after teardown
+    pub keep_temp: bool,
+    /// Each test writes one timestamped log file per process to `<logs_dir>/<test name>/`
+    pub logs_dir: PathBuf,
 }
 
 impl Default for TestRunnerConfig {
@@ -31,6 +59,11 @@ impl Default for TestRunnerConfig {
             rindexer_binary: "../rindexer/target/release/rindexer_cli".to_string(),
             anvil_port: 8545,
             health_port: 8080,
+            graphql_port: 3001,
+            jobs: 1,
+            artifacts_dir: PathBuf::from("target/e2e-artifacts"),
+            keep_temp: false,
+            logs_dir: PathBuf::from("target/e2e-logs"),
         }
     }
 }
@@ -49,8 +82,7 @@ impl TestRunner {
 
         let registry_tests = TestRegistry::get_all_tests();
 
-        for test_def in registry_tests {
-            let test_result = self.run_registry_test(&test_def).await;
+        for test_result in self.run_test_list(registry_tests).await {
             suite.add_test(test_result);
         }
 
@@ -59,22 +91,21 @@ impl TestRunner {
         Ok(suite)
     }
 
-    pub async fn run_filtered_tests(&self, test_names: &[String]) -> Result<TestSuite> {
-        info!("[START] Rindexer E2E Test Suite - Filtered Tests: {:?}", test_names);
+    pub async fn run_selected_tests(&self, selection: &TestSelection) -> Result<TestSuite> {
+        info!("[START] Rindexer E2E Test Suite - Selected Tests: {}", selection);
         info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
-        let mut suite = TestSuite::new(format!("Filtered Tests: {:?}", test_names));
+        let mut suite = TestSuite::new(format!("Selected Tests: {}", selection));
         let overall_start = Instant::now();
 
-        let registry_tests = TestRegistry::get_tests_by_filter(test_names);
+        let registry_tests = TestRegistry::get_tests_by_selection(selection);
 
         if registry_tests.is_empty() {
-            info!("No tests found matching filter: {:?}", test_names);
+            info!("No tests found matching selection: {}", selection);
             return Ok(suite);
         }
 
-        for test_def in registry_tests {
-            let test_result = self.run_registry_test(&test_def).await;
+        for test_result in self.run_test_list(registry_tests).await {
             suite.add_test(test_result);
         }
 
@@ -83,49 +114,199 @@ impl TestRunner {
         Ok(suite)
     }
 
-    async fn run_registry_test(&self, test_def: &TestDefinition) -> TestInfo {
+    /// Run the given tests, serially or across `jobs` parallel workers, returning results in input order
+    async fn run_test_list(&self, tests: Vec<TestDefinition>) -> Vec<TestInfo> {
+        // Probe prerequisites once so tests that cannot run are skipped before any setup
+        let capabilities = Arc::new(CapabilityReport::probe(&tests, &self.config.rindexer_binary).await);
+
+        if self.config.jobs <= 1 {
+            let ports = TestPorts::fixed(self.config.anvil_port, self.config.health_port, self.config.graphql_port);
+            let mut results = Vec::with_capacity(tests.len());
+            for test_def in tests {
+                results.push(self.run_registry_test(&test_def, ports, &capabilities).await);
+            }
+            return results;
+        }
+
+        info!("Running {} tests with {} parallel jobs", tests.len(), self.config.jobs);
+
+        let semaphore = Arc::new(Semaphore::new(self.config.jobs));
+        let mut join_set = JoinSet::new();
+        let mut task_ids = HashMap::new();
+        let mut results: Vec<Option<TestInfo>> = Vec::with_capacity(tests.len());
+
+        for (index, test_def) in tests.into_iter().enumerate() {
+            let runner = self.clone();
+            let semaphore = semaphore.clone();
+            let capabilities = capabilities.clone();
+            let name = test_def.name;
+            let handle = join_set.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("test semaphore closed");
+                let test_info = match TestPorts::allocate() {
+                    Ok(ports) => runner.run_registry_test(&test_def, ports, &capabilities).await,
+                    Err(e) => TestInfo::new(
+                        test_def.name.to_string(),
+                        TestResult::Failed(format!("Failed to allocate test ports: {}", e)),
+                        Duration::ZERO,
+                    ),
+                };
+                (index, test_info)
+            });
+            task_ids.insert(handle.id(), (index, name));
+            results.push(None);
+        }
+
+        while let Some(joined) = join_set.join_next().await {
+            match joined {
+                Ok((index, test_info)) => results[index] = Some(test_info),
+                Err(e) => {
+                    if let Some((index, name)) = task_ids.get(&e.id()) {
+                        error!("Test task for {} aborted: {}", name, e);
+                        results[*index] = Some(TestInfo::new(
+                            name.to_string(),
+                            TestResult::Failed(format!("Test task aborted: {}", e)),
+                            Duration::ZERO,
+                        ));
+                    }
+                }
+            }
+        }
+
+        results.into_iter().flatten().collect()
+    }
+
+    async fn run_registry_test(&self, test_def: &TestDefinition, ports: TestPorts, capabilities: &CapabilityReport) -> TestInfo {
         println!("[TEST] {} ... ", test_def.description);
+
+        if let Some(reason) = capabilities.skip_reason(test_def) {
+            println!("[SKIP] SKIPPED {}", test_def.name);
+            return TestInfo::new(test_def.name.to_string(), TestResult::Skipped(reason), Duration::ZERO);
+        }
+
         let start = Instant::now();
 
+        // The context lives outside the timed future so that teardown still
+        // runs when the test fails, panics or is cancelled by the timeout.
+        let mut context: Option<TestContext> = None;
         let result = timeout(
             Duration::from_secs(test_def.timeout_seconds),
-            self.run_single_test(test_def)
+            AssertUnwindSafe(self.run_single_test(test_def, ports, &mut context)).catch_unwind(),
         ).await;
 
         let test_result = match result {
-            Ok(Ok(())) => {
-                println!("[SUCCESS] PASS");
+            Ok(Ok(Ok(()))) => {
+                println!("[SUCCESS] PASS {}", test_def.name);
                 TestResult::Passed
             }
-            Ok(Err(e)) => {
+            Ok(Ok(Err(e))) => {
                 if let Some(skip) = e.downcast_ref::<SkipTest>() {
-                    println!("[SKIP] SKIPPED");
+                    println!("[SKIP] SKIPPED {}", test_def.name);
                     TestResult::Skipped(skip.0.clone())
                 } else {
-                    println!("[ERROR] FAIL");
+                    println!("[ERROR] FAIL {}", test_def.name);
                     TestResult::Failed(e.to_string())
                 }
             }
+            Ok(Err(panic)) => {
+                println!("[ERROR] PANIC {}", test_def.name);
+                TestResult::Failed(format!("Test panicked: {}", panic_message(&panic)))
+            }
             Err(_) => {
-                println!("[TIMEOUT] TIMEOUT");
+                println!("[TIMEOUT] TIMEOUT {}", test_def.name);
                 TestResult::Timeout
             }
         };
 
         let duration = start.elapsed();
+
+        // Collect the debug bundle while processes and containers are still up
+        let mut artifacts_path = None;
+        if matches!(test_result, TestResult::Failed(_) | TestResult::Timeout) {
+            if let Some(context) = context.as_ref() {
+                let dest = self.config.artifacts_dir.join(test_def.name);
+                match collect_failure_artifacts(context, &dest).await {
+                    Ok(()) => artifacts_path = Some(dest),
+                    Err(e) => error!("Failed to collect artifacts for {}: {}", test_def.name, e),
+                }
+            }
+        }
+
+        let teardown_errors = match context.as_mut() {
+            Some(context) => Self::teardown(test_def, context).await,
+            None => Vec::new(),
+        };
+        let leaks = Self::check_leaks(test_def, ports, context.as_ref()).await;
+
         TestInfo::new(test_def.name.to_string(), test_result, duration)
+            .with_teardown_errors(teardown_errors)
+            .with_leaks(leaks)
+            .with_artifacts(artifacts_path)
     }
 
-    async fn run_single_test(&self, test_def: &TestDefinition) -> Result<()> {
+    /// Always-run teardown, bounded so a stuck process cannot hang the whole run
+    async fn teardown(test_def: &TestDefinition, context: &mut TestContext) -> Vec<String> {
+        match timeout(TEARDOWN_TIMEOUT, context.cleanup()).await {
+            Ok(errors) => {
+                for e in &errors {
+                    error!("Teardown error in {}: {}", test_def.name, e);
+                }
+                errors
+            }
+            Err(_) => {
+                error!("Teardown of {} timed out", test_def.name);
+                vec![format!("Teardown timed out after {}s", TEARDOWN_TIMEOUT.as_secs())]
+            }
+        }
+    }
+
+    /// Anything the test started that outlived its teardown. Leaked process
+    /// groups and containers are removed here so they cannot disturb later tests.
+    async fn check_leaks(test_def: &TestDefinition, ports: TestPorts, context: Option<&TestContext>) -> Vec<String> {
+        let mut leaks = Vec::new();
+        for group in reap_leaked_groups(test_def.name, LEAK_GRACE).await {
+            leaks.push(format!("{} was still running and has been killed", group));
+        }
+
+        // Without a context the test never got as far as starting services on its ports
+        if let Some(context) = context {
+            for container in &context.postgres_containers {
+                if container_exists(container) {
+                    let _ = stop_postgres_container(container).await;
+                    leaks.push(format!("Container {} was still present and has been removed", container));
+                }
+            }
+            let mut services = vec![
+                ("anvil".to_string(), ports.anvil),
+                ("health".to_string(), ports.health),
+                ("graphql".to_string(), ports.graphql),
+            ];
+            services.extend(context.anvils.iter().map(|(network, anvil)| (format!("anvil-{}", network), anvil.port)));
+            for (service, port) in services {
+                if port_accepting(port).await {
+                    leaks.push(format!("Port {} ({}) is still accepting connections", port, service));
+                }
+            }
+        }
+
+        for leak in &leaks {
+            error!("Leak after {}: {}", test_def.name, leak);
+        }
+        leaks
+    }
+
+    async fn run_single_test(&self, test_def: &TestDefinition, ports: TestPorts, slot: &mut Option<TestContext>) -> Result<()> {
         // Create fresh test context for each test
-        let mut context = TestContext::new(
+        let logs = ProcessLogs::in_dir(test_def.name, &self.config.logs_dir.join(test_def.name))?;
+        let context = slot.insert(TestContext::new(
             self.config.rindexer_binary.clone(),
-            self.config.anvil_port,
-            self.config.health_port,
-        ).await?;
+            ports,
+            logs,
+            test_def.anvil.clone(),
+        ).await?);
+        context.keep_temp = self.config.keep_temp;
 
         // Start live feeder if this is a live test
-        let mut live_feeder = if test_def.is_live_test {
+        if test_def.is_live_test {
             info!("Starting live feeder for live indexing test: {}", test_def.name);
             let contract_address = context.deploy_test_contract().await?;
             
@@ -134,28 +315,32 @@ impl TestRunner {
             
             let mut feeder = LiveFeeder::new(
                 context.anvil.rpc_url.clone(),
-                "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".to_string(),
+                context.anvil.deployer().private_key,
             ).with_contract(contract_address.parse()?);
             
-            // Start feeder in background
+            // Start feeder in background; the context stops it on teardown
             feeder.start().await?;
-            Some(feeder)
-        } else {
-            None
-        };
-
-        // Run the actual test
-        let test_result = (test_def.function)(&mut context).await;
-
-        // Stop live feeder if it was running
-        if let Some(feeder) = live_feeder.take() {
-            info!("Stopping live feeder for test: {}", test_def.name);
-            feeder.stop();
+            context.live_feeder = Some(feeder);
         }
 
-        // Cleanup context
-        let _ = context.cleanup().await;
+        // Run the actual test, failing it as soon as a process it relies on crashes
+        let crash_monitor = context.crash_monitor.clone();
+        tokio::select! {
+            result = (test_def.function)(context) => result,
+            crash = crash_monitor.crashed() => {
+                error!("Aborting {}: {}", test_def.name, crash);
+                Err(anyhow::anyhow!(crash))
+            }
+        }
+    }
+}
 
-        test_result
+fn panic_message(panic: &Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
     }
 }
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -19,22 +20,44 @@ pub struct TestInfo {
     pub name: String,
     pub result: TestResult,
     pub duration: Duration,
+    /// Problems hit while tearing the test down, reported separately from the result
+    pub teardown_errors: Vec<String>,
+    /// Processes, containers or ports the test left behind after teardown
+    pub leaks: Vec<String>,
+    /// Debug bundle collected when the test failed or timed out
+    pub artifacts_path: Option<PathBuf>,
 }
 
 impl TestInfo {
     pub fn new(name: String, result: TestResult, duration: Duration) -> Self {
-        Self { name, result, duration }
+        Self { name, result, duration, teardown_errors: Vec::new(), leaks: Vec::new(), artifacts_path: None }
+    }
+
+    pub fn with_teardown_errors(mut self, teardown_errors: Vec<String>) -> Self {
+        self.teardown_errors = teardown_errors;
+        self
+    }
+
+    pub fn with_leaks(mut self, leaks: Vec<String>) -> Self {
+        self.leaks = leaks;
+        self
+    }
+
+    pub fn with_artifacts(mut self, artifacts_path: Option<PathBuf>) -> Self {
+        self.artifacts_path = artifacts_path;
+        self
     }
 }
 
 pub struct TestSuite {
+    pub name: String,
     pub tests: Vec<TestInfo>,
     pub duration: Duration,
 }
 
 impl TestSuite {
-    pub fn new(_name: String) -> Self {
-        Self { tests: Vec::new(), duration: Duration::ZERO }
+    pub fn new(name: String) -> Self {
+        Self { name, tests: Vec::new(), duration: Duration::ZERO }
     }
 
     pub fn add_test(&mut self, test: TestInfo) {
@@ -62,6 +85,14 @@ impl TestSuite {
         self.tests.len()
     }
 
+    pub fn teardown_error_count(&self) -> usize {
+        self.tests.iter().filter(|t| !t.teardown_errors.is_empty()).count()
+    }
+
+    pub fn leak_count(&self) -> usize {
+        self.tests.iter().filter(|t| !t.leaks.is_empty()).count()
+    }
+
     pub fn print_summary(&self) {
         println!();
         println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -116,6 +147,36 @@ impl TestSuite {
             }
         }
 
+        if self.teardown_error_count() > 0 {
+            println!();
+            println!("Teardown Errors:");
+            for test in &self.tests {
+                for e in &test.teardown_errors {
+                    println!("  [TEARDOWN] {} - {}", test.name, e);
+                }
+            }
+        }
+
+        if self.leak_count() > 0 {
+            println!();
+            println!("Leaked Resources:");
+            for test in &self.tests {
+                for leak in &test.leaks {
+                    println!("  [LEAK] {} - {}", test.name, leak);
+                }
+            }
+        }
+
+        if self.tests.iter().any(|t| t.artifacts_path.is_some()) {
+            println!();
+            println!("Failure Artifacts:");
+            for test in &self.tests {
+                if let Some(path) = &test.artifacts_path {
+                    println!("  [ARTIFACTS] {} - {}", test.name, path.display());
+                }
+            }
+        }
+
         println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
         if failed == 0 && timeout == 0 {
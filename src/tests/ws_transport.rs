@@ -0,0 +1,181 @@
+use anyhow::Result;
+use tracing::info;
+use std::pin::Pin;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::anvil_setup::{AnvilOptions, Mining, TRANSFER_TOPIC};
+use crate::test_suite::{RpcTransport, TestContext};
+use crate::tests::registry::{TestDefinition, TestModule, TestTag};
+use crate::ws_proxy::WsProxy;
+
+/// Transfers on the chain before the historic test starts rindexer, besides the constructor mint
+const HISTORIC_TRANSFERS: u64 = 10;
+
+/// New live events required before and after every interruption
+const LIVE_EVENTS: usize = 3;
+
+/// How long the outage test refuses every WebSocket connection
+const PARTITION: Duration = Duration::from_secs(10);
+
+/// How long rindexer gets to open a new WebSocket after losing its connection
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct WsTransportTests;
+
+impl TestModule for WsTransportTests {
+    fn get_tests() -> Vec<TestDefinition> {
+        vec![
+            TestDefinition::new(
+                "test_ws_historic_indexing",
+                "Historic sync over a ws:// RPC endpoint indexes every Transfer",
+                historic_test,
+            ).with_timeout(120)
+             .with_tags(&[TestTag::Historic])
+             .deploys(&["SimpleERC20"])
+             .with_anvil(AnvilOptions::new().with_mining(Mining::Auto)),
+            TestDefinition::new(
+                "test_ws_live_indexing",
+                "Live indexing over a ws:// RPC endpoint keeps up with the feeder without losing events",
+                live_test,
+            ).with_timeout(120)
+             .as_live_test(),
+            TestDefinition::new(
+                "test_ws_reconnect_live",
+                "Live indexing reconnects after every WebSocket is closed mid-feed and loses no events",
+                reconnect_test,
+            ).with_timeout(180)
+             .as_live_test(),
+            TestDefinition::new(
+                "test_ws_outage_live",
+                "Live indexing reconnects after WebSocket connections are refused for 10s and loses no events",
+                outage_test,
+            ).with_timeout(180)
+             .as_live_test(),
+        ]
+    }
+}
+
+/// What happens to rindexer's WebSocket in the middle of the live feed
+#[derive(Debug, Clone, Copy)]
+enum Interruption {
+    None,
+    /// Every open connection is closed; reconnecting succeeds at once
+    Disconnect,
+    /// Every open connection is closed and new ones are refused for `PARTITION`
+    Outage,
+}
+
+fn historic_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        info!("Running WebSocket Historic Indexing Test");
+
+        let contract_address = context.deploy_test_contract().await?;
+        context.send_transfers(&contract_address, HISTORIC_TRANSFERS).await?;
+
+        let proxy = context.start_ws_proxy("anvil").await?;
+        let mut config = context.create_contract_config(&contract_address);
+        config.networks[0].set_rpc(&proxy.url, RpcTransport::WebSocket)?;
+        context.start_rindexer(config).await?;
+        context.wait_for_sync_completion(60).await?;
+        require_upgrade(&proxy)?;
+
+        let canonical = context.anvil.get_logs(&contract_address, TRANSFER_TOPIC).await?;
+        context.stop_and_compare(&canonical).await?;
+        info!("✓ WebSocket Historic Indexing Test PASSED: {} connections", proxy.stats().connections);
+        Ok(())
+    })
+}
+
+fn live_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(live_over_ws(context, Interruption::None))
+}
+
+fn reconnect_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(live_over_ws(context, Interruption::Disconnect))
+}
+
+fn outage_test(context: &mut TestContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(live_over_ws(context, Interruption::Outage))
+}
+
+/// Index the live feed over a WebSocket proxy, interrupting the connection once
+/// live events are flowing; events must keep arriving afterwards and the CSV
+/// must match the chain exactly
+async fn live_over_ws(context: &mut TestContext, interruption: Interruption) -> Result<()> {
+    info!("Running WebSocket Live Test: {:?}", interruption);
+
+    let contract_address = context.test_contract_address.clone()
+        .ok_or_else(|| anyhow::anyhow!("No contract address available for live test"))?;
+
+    let proxy = context.start_ws_proxy("anvil").await?;
+    let mut config = context.create_contract_config(&contract_address);
+    config.networks[0].set_rpc(&proxy.url, RpcTransport::WebSocket)?;
+    context.start_rindexer(config).await?;
+    context.wait_for_sync_completion(60).await?;
+    require_upgrade(&proxy)?;
+
+    let before = context.get_event_count()?;
+    context.wait_for_new_events(before + LIVE_EVENTS, 30).await?;
+
+    let upgrades = proxy.stats().upgrades;
+    match interruption {
+        Interruption::None => {}
+        Interruption::Disconnect => {
+            proxy.disconnect();
+        }
+        Interruption::Outage => {
+            proxy.partition();
+            tokio::time::sleep(PARTITION).await;
+            proxy.heal();
+        }
+    }
+    if !matches!(interruption, Interruption::None) {
+        if proxy.stats().dropped == 0 {
+            return Err(anyhow::anyhow!("Rindexer had no WebSocket open to interrupt"));
+        }
+        wait_for_reconnect(&proxy, upgrades).await?;
+    }
+
+    // Events mined after the interruption have to come in over the new connection
+    let resumed = context.get_event_count()?;
+    context.wait_for_new_events(resumed + LIVE_EVENTS, 30).await?;
+    if let Some(feeder) = context.live_feeder.take() {
+        feeder.stop();
+    }
+    tokio::time::sleep(Duration::from_secs(5)).await;
+
+    let canonical = context.anvil.get_logs(&contract_address, TRANSFER_TOPIC).await?;
+    context.stop_and_compare(&canonical).await?;
+    let stats = proxy.stats();
+    info!(
+        "✓ WebSocket Live Test PASSED ({:?}): {} connections, {} dropped, {} refused, no events lost",
+        interruption, stats.connections, stats.dropped, stats.refused
+    );
+    Ok(())
+}
+
+/// Rindexer must have talked WebSocket through the proxy, not plain HTTP
+fn require_upgrade(proxy: &WsProxy) -> Result<()> {
+    let stats = proxy.stats();
+    if stats.upgrades == 0 {
+        return Err(anyhow::anyhow!(
+            "Rindexer made {} connections to the WebSocket proxy without a WebSocket upgrade",
+            stats.connections
+        ));
+    }
+    Ok(())
+}
+
+/// Wait until rindexer opens a WebSocket beyond the first `upgrades`
+async fn wait_for_reconnect(proxy: &WsProxy, upgrades: u64) -> Result<()> {
+    let started = Instant::now();
+    while proxy.stats().upgrades <= upgrades {
+        if started.elapsed() > RECONNECT_TIMEOUT {
+            return Err(anyhow::anyhow!("Rindexer did not reconnect within {:?}", RECONNECT_TIMEOUT));
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    info!("Rindexer reconnected after {:.1}s", started.elapsed().as_secs_f64());
+    Ok(())
+}
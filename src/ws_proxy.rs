@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::AbortHandle;
+use tracing::info;
+
+use crate::ports::allocate_free_port;
+use crate::process_log::ProcessLog;
+
+/// What the WebSocket proxy has seen so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WsProxyStats {
+    /// Connections relayed to the upstream
+    pub connections: u64,
+    /// Relayed connections that opened with a WebSocket upgrade request
+    pub upgrades: u64,
+    /// Open connections closed by `disconnect` or `partition`
+    pub dropped: u64,
+    /// Connections refused while partitioned
+    pub refused: u64,
+}
+
+struct WsProxyState {
+    /// `host:port` of the upstream
+    upstream: String,
+    partitioned: AtomicBool,
+    stats: Mutex<WsProxyStats>,
+    accept: Mutex<Option<AbortHandle>>,
+    /// Every relayed connection, aborted to drop it
+    connections: Mutex<Vec<AbortHandle>>,
+    log: ProcessLog,
+}
+
+/// In-process TCP relay in front of a WebSocket JSON-RPC endpoint. Frames pass
+/// through untouched; the relay only notices the upgrade request and can cut
+/// every connection, the way a load balancer restart or flaky network would.
+/// Clones share the same proxy.
+#[derive(Clone)]
+pub struct WsProxy {
+    /// Point rindexer here instead of at the upstream
+    pub url: String,
+    state: Arc<WsProxyState>,
+}
+
+impl WsProxy {
+    /// Listen on a free local port and relay to the `ws://host:port` endpoint `upstream`
+    pub async fn start(upstream: &str, log: ProcessLog) -> Result<Self> {
+        let address = upstream.strip_prefix("ws://")
+            .ok_or_else(|| anyhow::anyhow!("WebSocket proxy needs a ws:// upstream, got {}", upstream))?
+            .trim_end_matches('/')
+            .to_string();
+        let port = allocate_free_port()?;
+        let listener = TcpListener::bind(("127.0.0.1", port)).await
+            .with_context(|| format!("Failed to bind WebSocket proxy on port {}", port))?;
+        let state = Arc::new(WsProxyState {
+            upstream: address,
+            partitioned: AtomicBool::new(false),
+            stats: Mutex::new(WsProxyStats::default()),
+            accept: Mutex::new(None),
+            connections: Mutex::new(Vec::new()),
+            log,
+        });
+
+        let accept_state = state.clone();
+        let accept = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                if accept_state.partitioned.load(Ordering::SeqCst) {
+                    accept_state.stats.lock().unwrap().refused += 1;
+                    continue;
+                }
+                let state = accept_state.clone();
+                let task = tokio::spawn(async move {
+                    if let Err(e) = relay(stream, &state).await {
+                        state.log.push_stdout(&format!("connection ended: {}", e));
+                    }
+                });
+                let mut connections = accept_state.connections.lock().unwrap();
+                connections.retain(|connection| !connection.is_finished());
+                connections.push(task.abort_handle());
+            }
+        });
+        *state.accept.lock().unwrap() = Some(accept.abort_handle());
+
+        let url = format!("ws://127.0.0.1:{}", port);
+        info!("WebSocket proxy {} -> {}", url, upstream);
+        state.log.push_stdout(&format!("proxying {} -> {}", url, upstream));
+        Ok(Self { url, state })
+    }
+
+    /// Close every open connection; new ones are relayed as before.
+    /// Returns how many were closed.
+    pub fn disconnect(&self) -> u64 {
+        let mut dropped = 0;
+        for connection in self.state.connections.lock().unwrap().drain(..) {
+            if !connection.is_finished() {
+                dropped += 1;
+            }
+            connection.abort();
+        }
+        self.state.stats.lock().unwrap().dropped += dropped;
+        info!("WebSocket proxy {} closed {} connections", self.url, dropped);
+        self.state.log.push_stdout(&format!("disconnect: closed {} connections", dropped));
+        dropped
+    }
+
+    /// Close every open connection and refuse new ones until `heal` is called
+    pub fn partition(&self) {
+        info!("WebSocket proxy {} partitioned", self.url);
+        self.state.log.push_stdout("partition");
+        self.state.partitioned.store(true, Ordering::SeqCst);
+        self.disconnect();
+    }
+
+    pub fn heal(&self) {
+        info!("WebSocket proxy {} healed", self.url);
+        self.state.log.push_stdout("heal");
+        self.state.partitioned.store(false, Ordering::SeqCst);
+    }
+
+    pub fn stats(&self) -> WsProxyStats {
+        *self.state.stats.lock().unwrap()
+    }
+
+    /// Stop listening and close every open connection
+    pub fn stop(&self) {
+        if let Some(accept) = self.state.accept.lock().unwrap().take() {
+            accept.abort();
+        }
+        for connection in self.state.connections.lock().unwrap().drain(..) {
+            connection.abort();
+        }
+    }
+}
+
+/// Pipe `client` to the upstream until either side closes
+async fn relay(mut client: TcpStream, state: &WsProxyState) -> Result<()> {
+    let mut upstream = TcpStream::connect(&state.upstream).await
+        .with_context(|| format!("Failed to connect to {}", state.upstream))?;
+
+    // The handshake is the first thing the client sends
+    let mut first = vec![0u8; 8192];
+    let read = client.read(&mut first).await?;
+    let handshake = String::from_utf8_lossy(&first[..read]).to_ascii_lowercase();
+    let upgrade = handshake.lines().any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| name.trim() == "upgrade" && value.trim() == "websocket")
+    });
+    {
+        let mut stats = state.stats.lock().unwrap();
+        stats.connections += 1;
+        if upgrade {
+            stats.upgrades += 1;
+        }
+    }
+    state.log.push_stdout(&format!(
+        "connection {}: {}",
+        if upgrade { "upgraded" } else { "opened" },
+        handshake.lines().next().unwrap_or_default()
+    ));
+
+    upstream.write_all(&first[..read]).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}